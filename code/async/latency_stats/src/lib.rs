@@ -0,0 +1,22 @@
+//! The two sums every benchmark here needs, to turn a pile of timings
+//! into a table.
+
+use std::time::Duration;
+
+/// The `pct`th percentile (0 to 100) of `sorted`, which must be sorted.
+/// Nearest-rank: the smallest sample that at least `pct`% of them are no
+/// bigger than. So it's always one of the samples. `ZERO` if there are
+/// none.
+pub fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    // Ranks count from 1; the 0th percentile is the smallest sample.
+    let rank = (pct * sorted.len() as f64 / 100.0).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// `d` in milliseconds, with the fraction - for printing.
+pub fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,24 @@
+use latency_stats::{millis, percentile};
+use std::time::Duration;
+
+#[test]
+fn percentiles_are_nearest_rank() {
+    let samples: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+    let at = |pct| percentile(&samples, pct).as_millis();
+    assert_eq!(at(0.0), 1);
+    assert_eq!(at(10.0), 1);
+    assert_eq!(at(11.0), 2);
+    assert_eq!(at(50.0), 5);
+    assert_eq!(at(90.0), 9);
+    assert_eq!(at(95.0), 10);
+    assert_eq!(at(100.0), 10);
+
+    let samples: Vec<Duration> = (1..=1000).map(Duration::from_millis).collect();
+    assert_eq!(percentile(&samples, 99.9), Duration::from_millis(999));
+    assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+}
+
+#[test]
+fn millis_keeps_the_fraction() {
+    assert_eq!(millis(Duration::from_micros(1500)), 1.5);
+}
@@ -0,0 +1,42 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::ItemFn;
+
+// This is (a much simpler version of) what `#[tokio::main]` does.
+//
+// It turns:
+//
+// async fn main() { ... }
+//
+// into:
+//
+// fn main() { mini_executor::block_on(async { ... }) }
+//
+// Any arguments (such as `flavor = "current_thread"`) are accepted and
+// ignored: this executor only has one flavor.
+#[proc_macro_attribute]
+pub fn main(_args: TokenStream, item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as ItemFn);
+
+    if input.sig.asyncness.is_none() {
+        return syn::Error::new_spanned(input.sig.fn_token, "the `async` keyword is missing from the function declaration")
+            .to_compile_error()
+            .into();
+    }
+
+    let attrs = &input.attrs;
+    let vis = &input.vis;
+    let body = &input.block;
+    let mut sig = input.sig.clone();
+    sig.asyncness = None;
+
+    quote! {
+        #(#attrs)*
+        #vis #sig {
+            ::mini_executor::block_on(async move #body)
+        }
+    }
+    .into()
+}
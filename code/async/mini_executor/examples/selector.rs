@@ -0,0 +1,44 @@
+// The original `code/async/selector` example, running on
+// `mini_executor`. (The current one also listens for Ctrl-C, which needs
+// `tokio::signal` - and that needs Tokio's runtime.)
+//
+// Besides the runtime import below, the changes are both to `send`: the
+// ticker checks what it returns, so it stops once nobody is listening,
+// and the quitter ignores it with `let _ =` (the original's unused
+// `Result`s were warnings).
+use mini_executor as tokio;
+
+async fn ticker(tx: tokio::sync::mpsc::Sender<i32>) {
+    loop {
+        if tx.send(1).await.is_err() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs_f32(0.1)).await;
+    }
+}
+
+async fn quitter(tx: tokio::sync::mpsc::Sender<i32>) {
+    tokio::time::sleep(std::time::Duration::from_secs_f32(0.5)).await;
+    let _ = tx.send(0).await;
+}
+
+#[tokio::main]
+async fn main() {
+    let (tx1, mut rx1) = tokio::sync::mpsc::channel::<i32>(10);
+    let (tx2, mut rx2) = tokio::sync::mpsc::channel::<i32>(10);
+
+    tokio::spawn(ticker(tx1));
+    tokio::spawn(quitter(tx2));
+
+    loop {
+        tokio::select! {
+            Some(val) = rx1.recv() => {
+                println!("From 1: {val}");
+            }
+            Some(..) = rx2.recv() => {
+                break;
+            }
+        }
+    }
+    println!("Quitting");
+}
@@ -0,0 +1,30 @@
+// `code/async/thread_sleep`, running on `mini_executor`. `main` is the
+// same; the counters are copied in from its `lib.rs`, since the crate's
+// own counters call Tokio's `sleep`. Copied in, they aren't `pub`, and
+// `counter` needs `#[allow(dead_code)]` until you swap it in. Otherwise
+// the only change is the runtime import:
+use mini_executor as tokio;
+
+use std::time::Duration;
+
+// Swap this in for `correct_counter`: our executor is single-threaded
+// too, so it blocks everything just like it does on Tokio.
+#[allow(dead_code)]
+async fn counter(n: f32) {
+    std::thread::sleep(Duration::from_secs_f32(1.0 - n));
+    println!("{n}");
+}
+
+async fn correct_counter(n: f32) {
+    tokio::time::sleep(Duration::from_secs_f32(1.0 - n)).await;
+    println!("{n}");
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    for i in 0..10 {
+        tokio::spawn(correct_counter(i as f32 / 10.0));
+    }
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+}
@@ -0,0 +1,222 @@
+use crate::timer::TimerWheel;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+use std::time::Instant;
+
+// The id we use for the future passed to `block_on`. Spawned tasks
+// count up from zero.
+const MAIN_TASK: usize = usize::MAX;
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+// The part of the executor that wakers need to reach. Wakers must be
+// `Send + Sync` (a channel might be woken from another thread), so this
+// lives in an `Arc` and uses a `Mutex`.
+struct ReadyQueue {
+    thread: Thread,
+    ready: Mutex<VecDeque<usize>>,
+}
+
+impl ReadyQueue {
+    fn push(&self, id: usize) {
+        self.ready.lock().unwrap().push_back(id);
+        // If the executor thread is parked waiting for work, this
+        // wakes it up.
+        self.thread.unpark();
+    }
+
+    fn take_all(&self) -> VecDeque<usize> {
+        std::mem::take(&mut *self.ready.lock().unwrap())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ready.lock().unwrap().is_empty()
+    }
+}
+
+// Waking a task just means putting its id back in the ready queue.
+struct TaskWaker {
+    id: usize,
+    queue: Arc<ReadyQueue>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.queue.push(self.id);
+    }
+}
+
+// Everything else only lives on the executor thread, so it can use
+// `Rc` and `RefCell`.
+pub(crate) struct Runtime {
+    queue: Arc<ReadyQueue>,
+    tasks: RefCell<HashMap<usize, Task>>,
+    next_id: RefCell<usize>,
+    pub(crate) timers: RefCell<TimerWheel>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Runtime>>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn current() -> Rc<Runtime> {
+    CURRENT.with(|current| {
+        current
+            .borrow()
+            .clone()
+            .expect("must be called from inside `mini_executor::block_on`")
+    })
+}
+
+impl Runtime {
+    fn waker(&self, id: usize) -> Waker {
+        Waker::from(Arc::new(TaskWaker {
+            id,
+            queue: self.queue.clone(),
+        }))
+    }
+
+    fn poll_task(&self, id: usize) {
+        // Take the task out of the map while we poll it - it may want
+        // to spawn more tasks, which needs to borrow the map.
+        let Some(mut task) = self.tasks.borrow_mut().remove(&id) else {
+            // Already finished; this was a stale wake-up.
+            return;
+        };
+        let waker = self.waker(id);
+        let mut cx = Context::from_waker(&waker);
+        if task.as_mut().poll(&mut cx).is_pending() {
+            self.tasks.borrow_mut().insert(id, task);
+        }
+    }
+}
+
+/// Runs a future to completion on the current thread, along with any
+/// tasks it spawns. When the future finishes, any unfinished tasks are
+/// dropped - just like Tokio.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let runtime = Rc::new(Runtime {
+        queue: Arc::new(ReadyQueue {
+            thread: std::thread::current(),
+            ready: Mutex::new(VecDeque::new()),
+        }),
+        tasks: RefCell::new(HashMap::new()),
+        next_id: RefCell::new(0),
+        timers: RefCell::new(TimerWheel::new(Instant::now())),
+    });
+    CURRENT.with(|current| {
+        let mut current = current.borrow_mut();
+        assert!(current.is_none(), "`block_on` can't be called from inside `block_on`");
+        *current = Some(runtime.clone());
+    });
+    // Clears the thread-local (and drops leftover tasks) when we're
+    // done - even if a task panics.
+    struct Exit;
+    impl Drop for Exit {
+        fn drop(&mut self) {
+            let runtime = CURRENT.with(|current| current.borrow_mut().take());
+            if let Some(runtime) = runtime {
+                runtime.tasks.borrow_mut().clear();
+            }
+        }
+    }
+    let _exit = Exit;
+
+    let mut future = pin!(future);
+    let main_waker = runtime.waker(MAIN_TASK);
+    runtime.queue.push(MAIN_TASK);
+
+    loop {
+        // Poll everything that has been woken up.
+        for id in runtime.queue.take_all() {
+            if id == MAIN_TASK {
+                let mut cx = Context::from_waker(&main_waker);
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            } else {
+                runtime.poll_task(id);
+            }
+        }
+
+        // Fire any timers that are due. That wakes the tasks waiting on
+        // them, which puts them back in the ready queue.
+        let now = Instant::now();
+        runtime.timers.borrow_mut().advance(now);
+
+        // Nothing to do? Sleep until the next timer is due, or until a
+        // waker calls `unpark`.
+        if runtime.queue.is_empty() {
+            let next_timer = runtime.timers.borrow().next_deadline();
+            match next_timer {
+                Some(deadline) => std::thread::park_timeout(deadline.saturating_duration_since(now)),
+                None => std::thread::park(),
+            }
+        }
+    }
+}
+
+struct JoinState<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// Awaiting a `JoinHandle` gives you the spawned task's result.
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.borrow_mut();
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Adds a task to the executor. It starts running the next time the
+/// executor gets control - when the current task awaits something.
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    let runtime = current();
+    let state = Rc::new(RefCell::new(JoinState {
+        output: None,
+        waker: None,
+    }));
+
+    let task_state = state.clone();
+    let task = async move {
+        let output = future.await;
+        let mut state = task_state.borrow_mut();
+        state.output = Some(output);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    };
+
+    let id = {
+        let mut next_id = runtime.next_id.borrow_mut();
+        *next_id += 1;
+        *next_id - 1
+    };
+    runtime.tasks.borrow_mut().insert(id, Box::pin(task));
+    runtime.queue.push(id);
+
+    JoinHandle { state }
+}
@@ -0,0 +1,29 @@
+//! A tiny, single-threaded async executor - written to show what
+//! `#[tokio::main]`, `block_on`, `spawn` and `sleep` are doing under
+//! the hood. Don't use it for real work!
+//!
+//! It covers just enough of Tokio's API that simple examples only need
+//! their runtime import changed:
+//!
+//! ```ignore
+//! use mini_executor as tokio;
+//! ```
+mod executor;
+mod timer;
+
+pub use executor::{block_on, spawn, JoinHandle};
+pub use mini_executor_macros::main;
+
+/// Timers, driven by the executor's timer wheel.
+pub mod time {
+    pub use crate::timer::{sleep, Sleep};
+}
+
+/// Tokio's channels and locks don't depend on the Tokio runtime - they
+/// work with any executor, including this one.
+pub mod sync {
+    pub use tokio::sync::*;
+}
+
+// Likewise, `select!` only needs futures - not a runtime.
+pub use tokio::select;
@@ -0,0 +1,164 @@
+use crate::executor::current;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+// Each slot in the wheel covers one millisecond. A timer further away
+// than `SLOTS` ticks wraps around, and just waits for the wheel to come
+// round again.
+const TICK: Duration = Duration::from_millis(1);
+const SLOTS: usize = 256;
+
+// Shared between a `Sleep` and its entry in the wheel. When the `Sleep`
+// is dropped (say, because it lost a `select!`) the waker is cleared,
+// and the entry is discarded the next time its slot is checked.
+type SharedWaker = Rc<RefCell<Option<Waker>>>;
+
+struct Entry {
+    tick: u64,
+    waker: SharedWaker,
+}
+
+/// A hashed timer wheel. Adding a timer is O(1): it goes in the slot
+/// for its deadline. Each time the executor wakes up it only looks at
+/// the slots for the ticks that have passed since last time.
+pub(crate) struct TimerWheel {
+    start: Instant,
+    // The first tick that hasn't been processed yet.
+    next_tick: u64,
+    slots: Vec<Vec<Entry>>,
+}
+
+impl TimerWheel {
+    pub(crate) fn new(start: Instant) -> Self {
+        Self {
+            start,
+            next_tick: 0,
+            slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    // Rounds up, so a timer never fires before its deadline.
+    fn tick_for(&self, deadline: Instant) -> u64 {
+        let since_start = deadline.saturating_duration_since(self.start);
+        since_start.as_nanos().div_ceil(TICK.as_nanos()) as u64
+    }
+
+    fn insert(&mut self, deadline: Instant, waker: SharedWaker) {
+        // A deadline in a tick we've already processed goes in the next
+        // one, or it would have to wait for a full turn of the wheel.
+        let tick = self.tick_for(deadline).max(self.next_tick);
+        self.slots[tick as usize % SLOTS].push(Entry { tick, waker });
+    }
+
+    /// Wakes every timer that is due at `now`.
+    pub(crate) fn advance(&mut self, now: Instant) {
+        let now_tick = since_start_ticks(self.start, now);
+        if now_tick < self.next_tick {
+            return;
+        }
+        // If we've been away for a whole turn of the wheel, every slot
+        // needs checking - but only once.
+        let ticks_to_check = (now_tick - self.next_tick + 1).min(SLOTS as u64);
+        for offset in 0..ticks_to_check {
+            let slot = ((self.next_tick + offset) as usize) % SLOTS;
+            self.slots[slot].retain(|entry| {
+                if entry.tick > now_tick {
+                    // Not due yet: it's on a later turn of the wheel.
+                    // Keep it, unless its `Sleep` has been dropped.
+                    return entry.waker.borrow().is_some();
+                }
+                if let Some(waker) = entry.waker.borrow_mut().take() {
+                    waker.wake();
+                }
+                false
+            });
+        }
+        self.next_tick = now_tick + 1;
+    }
+
+    /// When the executor next needs to wake up for a timer, if any
+    /// timers are pending.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|entry| entry.waker.borrow().is_some())
+            .map(|entry| entry.tick)
+            .min()
+            .map(|tick| self.instant_for(tick))
+    }
+
+    // `TICK * tick as u32` would quietly wrap a big tick count round to
+    // a small one. Saturating instead means a very distant timer just
+    // wakes us up early - `advance` keeps it if it isn't due.
+    fn instant_for(&self, tick: u64) -> Instant {
+        let ticks = u32::try_from(tick).unwrap_or(u32::MAX);
+        let offset = TICK.checked_mul(ticks).unwrap_or(Duration::MAX);
+        saturating_add(self.start, offset)
+    }
+}
+
+// About 30 years: further off than anything here will wait, and close
+// enough that any `Instant` we'll see can have it added.
+const FAR_FUTURE: Duration = Duration::from_secs(30 * 365 * 24 * 60 * 60);
+
+// `Instant` has no `MAX` to saturate to, and `+` panics if the result
+// can't be represented. So a deadline past what it can hold becomes
+// `FAR_FUTURE` instead - as good as never.
+fn saturating_add(instant: Instant, duration: Duration) -> Instant {
+    instant
+        .checked_add(duration)
+        .or_else(|| instant.checked_add(FAR_FUTURE))
+        .unwrap_or(instant)
+}
+
+fn since_start_ticks(start: Instant, now: Instant) -> u64 {
+    (now.saturating_duration_since(start).as_nanos() / TICK.as_nanos()) as u64
+}
+
+/// The future returned by `sleep`.
+pub struct Sleep {
+    deadline: Instant,
+    waker: Option<SharedWaker>,
+}
+
+/// Waits until `duration` has passed, without blocking the thread.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: saturating_add(Instant::now(), duration),
+        waker: None,
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            // Already in the wheel - just make sure it has our latest
+            // waker.
+            Some(shared) => *shared.borrow_mut() = Some(cx.waker().clone()),
+            None => {
+                let shared = Rc::new(RefCell::new(Some(cx.waker().clone())));
+                current().timers.borrow_mut().insert(self.deadline, shared.clone());
+                self.waker = Some(shared);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(shared) = &self.waker {
+            shared.borrow_mut().take();
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use mini_executor::time::sleep;
+use mini_executor::{block_on, spawn};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn block_on_returns_the_output() {
+    assert_eq!(block_on(async { 6 * 7 }), 42);
+    // The runtime is gone afterwards, so it can be started again.
+    assert_eq!(block_on(async { "again" }), "again");
+}
+
+#[test]
+fn spawned_tasks_run_and_can_be_joined() {
+    let total = block_on(async {
+        let handles: Vec<_> = (1..=10).map(|n| spawn(async move { n * n })).collect();
+        let mut total = 0;
+        for handle in handles {
+            total += handle.await;
+        }
+        total
+    });
+    assert_eq!(total, 385);
+}
+
+#[test]
+fn timers_fire_in_deadline_order() {
+    let fired = Rc::new(RefCell::new(Vec::new()));
+    let start = Instant::now();
+    block_on({
+        let fired = fired.clone();
+        async move {
+            // Spawned out of order, and 300ms is more than one turn of
+            // the wheel.
+            let handles: Vec<_> = [30, 300, 10, 20]
+                .into_iter()
+                .map(|millis| {
+                    let fired = fired.clone();
+                    spawn(async move {
+                        sleep(Duration::from_millis(millis)).await;
+                        // Never early.
+                        assert!(start.elapsed() >= Duration::from_millis(millis));
+                        fired.borrow_mut().push(millis);
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.await;
+            }
+        }
+    });
+    assert_eq!(*fired.borrow(), [10, 20, 30, 300]);
+}
+
+#[test]
+fn a_dropped_sleep_does_not_hold_things_up() {
+    let start = Instant::now();
+    let winner = block_on(async {
+        mini_executor::select! {
+            _ = sleep(Duration::from_millis(10)) => "short",
+            _ = sleep(Duration::from_secs(60)) => "long",
+        }
+    });
+    assert_eq!(winner, "short");
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn sleeping_forever_does_not_panic() {
+    // `Instant::now() + Duration::MAX` would overflow.
+    let winner = block_on(async {
+        mini_executor::select! {
+            _ = sleep(Duration::from_millis(10)) => "short",
+            _ = sleep(Duration::MAX) => "forever",
+        }
+    });
+    assert_eq!(winner, "short");
+}
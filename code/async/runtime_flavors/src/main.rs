@@ -0,0 +1,228 @@
+// Runs the workloads from the async examples under different runtime
+// flavors, and prints a Markdown table you can paste into the manual.
+//
+// Pass the multi-thread worker counts to try, each at least 1 (the default
+// is one per CPU):
+// cargo run --release -p runtime_flavors -- 2 4
+//
+// Latency means different things for different workloads:
+// * spin, correct_counter: how late each timer woke the task up.
+// * find_prime: how long from spawning the task until it had an answer.
+// * spin + find_prime: the spin tasks' timer lateness, while the
+//   find_prime tasks hog the CPU.
+use latency_stats::{millis, percentile};
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use too_much_work::is_prime;
+
+const SPIN_TASKS: usize = 100;
+const SPIN_TICK: Duration = Duration::from_millis(10);
+const COUNTER_TASKS: usize = 100;
+// `thread_sleep` waits up to a second. We scale that down so the
+// harness doesn't take all day.
+const COUNTER_SCALE: Duration = Duration::from_millis(200);
+const PRIME_TASKS: usize = 32;
+const PRIME: usize = 999983;
+
+type Samples = Vec<Duration>;
+type Task = Pin<Box<dyn Future<Output = Samples> + Send>>;
+
+// `too_much_work::spin`, without the `println!` - and remembering how
+// late each sleep woke up.
+async fn spin() -> Samples {
+    let mut lateness = Vec::with_capacity(5);
+    for _ in 0..5 {
+        let start = Instant::now();
+        tokio::time::sleep(SPIN_TICK).await;
+        lateness.push(start.elapsed().saturating_sub(SPIN_TICK));
+    }
+    lateness
+}
+
+// `thread_sleep::correct_counter`, likewise.
+async fn correct_counter(n: f32) -> Samples {
+    let duration = COUNTER_SCALE.mul_f32(1.0 - n);
+    let start = Instant::now();
+    tokio::time::sleep(duration).await;
+    vec![start.elapsed().saturating_sub(duration)]
+}
+
+// `too_much_work::find_prime`, likewise.
+async fn find_prime(spawned: Instant) -> Samples {
+    assert!(is_prime(PRIME));
+    vec![spawned.elapsed()]
+}
+
+// `find_prime`, for when only the other tasks' latency matters.
+async fn find_prime_quietly() -> Samples {
+    assert!(is_prime(PRIME));
+    Vec::new()
+}
+
+#[derive(Clone, Copy)]
+enum Workload {
+    Spin,
+    CorrectCounter,
+    FindPrime,
+    SpinWithFindPrime,
+}
+
+impl Workload {
+    fn name(&self) -> &'static str {
+        match self {
+            Workload::Spin => "spin",
+            Workload::CorrectCounter => "correct_counter",
+            Workload::FindPrime => "find_prime",
+            Workload::SpinWithFindPrime => "spin + find_prime",
+        }
+    }
+
+    fn tasks(&self) -> Vec<Task> {
+        match self {
+            Workload::Spin => (0..SPIN_TASKS).map(|_| Box::pin(spin()) as Task).collect(),
+            Workload::CorrectCounter => (0..COUNTER_TASKS)
+                .map(|i| Box::pin(correct_counter(i as f32 / COUNTER_TASKS as f32)) as Task)
+                .collect(),
+            Workload::FindPrime => (0..PRIME_TASKS)
+                .map(|_| Box::pin(find_prime(Instant::now())) as Task)
+                .collect(),
+            Workload::SpinWithFindPrime => {
+                let mut tasks: Vec<Task> = (0..SPIN_TASKS).map(|_| Box::pin(spin()) as Task).collect();
+                tasks.extend((0..PRIME_TASKS).map(|_| Box::pin(find_prime_quietly()) as Task));
+                tasks
+            }
+        }
+    }
+}
+
+const WORKLOADS: [Workload; 4] = [
+    Workload::Spin,
+    Workload::CorrectCounter,
+    Workload::FindPrime,
+    Workload::SpinWithFindPrime,
+];
+
+#[derive(Clone, Copy)]
+enum Flavor {
+    CurrentThread,
+    MultiThread(usize),
+    LocalSet,
+}
+
+impl Flavor {
+    fn name(&self) -> String {
+        match self {
+            Flavor::CurrentThread => "current_thread".to_string(),
+            Flavor::MultiThread(workers) => format!("multi_thread ({workers} workers)"),
+            Flavor::LocalSet => "LocalSet".to_string(),
+        }
+    }
+}
+
+struct Report {
+    tasks: usize,
+    elapsed: Duration,
+    // Sorted, so we can read percentiles straight out of it.
+    latency: Samples,
+}
+
+// Spawns every task, then waits for them all.
+async fn drive(workload: Workload, local: bool) -> Report {
+    let tasks = workload.tasks();
+    let count = tasks.len();
+
+    let start = Instant::now();
+    let handles: Vec<_> = tasks
+        .into_iter()
+        .map(|task| {
+            if local {
+                tokio::task::spawn_local(task)
+            } else {
+                tokio::spawn(task)
+            }
+        })
+        .collect();
+    let mut latency = Vec::new();
+    for handle in handles {
+        latency.extend(handle.await.unwrap());
+    }
+    let elapsed = start.elapsed();
+
+    latency.sort();
+    Report {
+        tasks: count,
+        elapsed,
+        latency,
+    }
+}
+
+fn run(flavor: Flavor, workload: Workload) -> Report {
+    match flavor {
+        Flavor::CurrentThread => tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(drive(workload, false)),
+        Flavor::MultiThread(workers) => tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(workers)
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(drive(workload, false)),
+        Flavor::LocalSet => {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            let local = tokio::task::LocalSet::new();
+            runtime.block_on(local.run_until(drive(workload, true)))
+        }
+    }
+}
+
+fn main() {
+    // Tokio panics if asked for zero workers, so stop at the command line.
+    let worker_counts: Vec<usize> = std::env::args()
+        .skip(1)
+        .map(|arg| match arg.parse::<NonZeroUsize>() {
+            Ok(workers) => workers.get(),
+            Err(_) => {
+                eprintln!("`{arg}` isn't a worker count: they must be whole numbers, at least 1");
+                eprintln!("Usage: runtime_flavors [WORKER_COUNT]...");
+                std::process::exit(2);
+            }
+        })
+        .collect();
+    let worker_counts = if worker_counts.is_empty() {
+        vec![std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)]
+    } else {
+        worker_counts
+    };
+
+    let mut flavors = vec![Flavor::CurrentThread];
+    flavors.extend(worker_counts.into_iter().map(Flavor::MultiThread));
+    flavors.push(Flavor::LocalSet);
+
+    println!("Flavor|Workload|Tasks|Time (s)|Tasks/s|p50 (ms)|p90 (ms)|p99 (ms)|max (ms)");
+    println!("--|--|--|--|--|--|--|--|--");
+    for workload in WORKLOADS {
+        for flavor in &flavors {
+            let report = run(*flavor, workload);
+            let seconds = report.elapsed.as_secs_f64();
+            println!(
+                "{}|{}|{}|{:.3}|{:.0}|{:.3}|{:.3}|{:.3}|{:.3}",
+                flavor.name(),
+                workload.name(),
+                report.tasks,
+                seconds,
+                report.tasks as f64 / seconds,
+                millis(percentile(&report.latency, 50.0)),
+                millis(percentile(&report.latency, 90.0)),
+                millis(percentile(&report.latency, 99.0)),
+                millis(report.latency.last().copied().unwrap_or_default()),
+            );
+        }
+    }
+}
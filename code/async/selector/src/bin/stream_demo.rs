@@ -0,0 +1,74 @@
+// The selector example again - but with the channels turned into
+// streams, and the `select!` loop replaced by a pipeline.
+//
+// cargo run -p selector --bin stream_demo
+use selector::streams::{batch, debounce, from_channel, merge, throttle, timeout_each};
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+async fn ticker(tx: tokio::sync::mpsc::Sender<i32>) {
+    for _ in 0..10 {
+        if tx.send(1).await.is_err() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_secs_f32(0.1)).await;
+    }
+}
+
+async fn burst(tx: tokio::sync::mpsc::Sender<i32>) {
+    tokio::time::sleep(Duration::from_secs_f32(0.25)).await;
+    for i in 0..5 {
+        if tx.send(100 + i).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let (tx1, rx1) = tokio::sync::mpsc::channel::<i32>(10);
+    let (tx2, rx2) = tokio::sync::mpsc::channel::<i32>(10);
+    tokio::spawn(ticker(tx1));
+    tokio::spawn(burst(tx2));
+
+    // Merge both channels, and batch them up: at most 4 items, or
+    // whatever arrived within 250ms.
+    let batches = batch(
+        merge(from_channel(rx1), from_channel(rx2)),
+        4,
+        Duration::from_millis(250),
+    );
+    let mut batches = std::pin::pin!(batches);
+    while let Some(batch) = batches.next().await {
+        println!("Batch: {batch:?}");
+    }
+
+    // A burst of 5 items, throttled to one every 50ms.
+    let (tx, rx) = tokio::sync::mpsc::channel::<i32>(10);
+    tokio::spawn(burst(tx));
+    let mut throttled = std::pin::pin!(throttle(from_channel(rx), Duration::from_millis(50)));
+    while let Some(val) = throttled.next().await {
+        println!("Throttled: {val}");
+    }
+
+    // The same burst, debounced: only the last item survives.
+    let (tx, rx) = tokio::sync::mpsc::channel::<i32>(10);
+    tokio::spawn(burst(tx));
+    let mut debounced = std::pin::pin!(debounce(from_channel(rx), Duration::from_millis(50)));
+    while let Some(val) = debounced.next().await {
+        println!("Debounced: {val}");
+    }
+
+    // Ticks arrive every 100ms, so a 50ms timeout fires between them.
+    let (tx, rx) = tokio::sync::mpsc::channel::<i32>(10);
+    tokio::spawn(ticker(tx));
+    let timed = timeout_each(from_channel(rx), Duration::from_millis(50)).take(6);
+    let mut timed = std::pin::pin!(timed);
+    while let Some(val) = timed.next().await {
+        match val {
+            Ok(val) => println!("On time: {val}"),
+            Err(e) => println!("Late: {e}"),
+        }
+    }
+    println!("Quitting");
+}
@@ -1,13 +1,36 @@
-async fn ticker(tx: tokio::sync::mpsc::Sender<i32>) {
+use selector::shutdown::{Shutdown, ShutdownCoordinator};
+use std::time::Duration;
+
+async fn ticker(tx: tokio::sync::mpsc::Sender<i32>, mut shutdown: Shutdown) {
+    let mut sent = 0;
     loop {
-        tx.send(1).await;
-        tokio::time::sleep(std::time::Duration::from_secs_f32(0.1)).await;
+        tokio::select! {
+            _ = shutdown.recv() => break,
+            result = tx.send(1) => {
+                if result.is_err() {
+                    // The receiver is gone, nobody is listening.
+                    break;
+                }
+                sent += 1;
+            }
+        }
+        tokio::select! {
+            _ = shutdown.recv() => break,
+            _ = tokio::time::sleep(std::time::Duration::from_secs_f32(0.1)) => {}
+        }
     }
+    println!("Ticker stopping after {sent} ticks");
 }
 
-async fn quitter(tx: tokio::sync::mpsc::Sender<i32>) {
-        tokio::time::sleep(std::time::Duration::from_secs_f32(0.5)).await;
-        tx.send(0).await;
+async fn quitter(tx: tokio::sync::mpsc::Sender<i32>, mut shutdown: Shutdown) {
+    tokio::select! {
+        _ = shutdown.recv() => {}
+        _ = tokio::time::sleep(std::time::Duration::from_secs_f32(0.5)) => {
+            if tx.send(0).await.is_err() {
+                println!("Quitter couldn't send: the receiver is gone");
+            }
+        }
+    }
 }
 
 #[tokio::main]
@@ -15,8 +38,9 @@ async fn main() {
     let (tx1, mut rx1) = tokio::sync::mpsc::channel::<i32>(10);
     let (tx2, mut rx2) = tokio::sync::mpsc::channel::<i32>(10);
 
-    tokio::spawn(ticker(tx1));
-    tokio::spawn(quitter(tx2));
+    let mut coordinator = ShutdownCoordinator::new();
+    coordinator.spawn("ticker", |shutdown| ticker(tx1, shutdown));
+    coordinator.spawn("quitter", |shutdown| quitter(tx2, shutdown));
 
     loop {
         tokio::select! {
@@ -26,7 +50,22 @@ async fn main() {
             Some(..) = rx2.recv() => {
                 break;
             }
+            _ = tokio::signal::ctrl_c() => {
+                break;
+            }
         }
     }
     println!("Quitting");
-}
\ No newline at end of file
+
+    let stragglers = coordinator.shutdown(Duration::from_secs(1)).await;
+    if !stragglers.is_empty() {
+        println!("These tasks didn't stop in time: {stragglers:?}");
+    }
+
+    // Every sender has been dropped now, so this drains whatever was
+    // still in flight and then ends.
+    while let Some(val) = rx1.recv().await {
+        println!("From 1 (flushed): {val}");
+    }
+    println!("All tasks finished");
+}
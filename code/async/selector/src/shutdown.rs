@@ -0,0 +1,110 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+/// Handed to every task spawned by the `ShutdownCoordinator`. Tasks
+/// select on `recv()` alongside their real work, and exit when it
+/// completes.
+#[derive(Clone)]
+pub struct Shutdown {
+    receiver: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Waits until shutdown has been requested. Returns immediately if
+    /// it already has been.
+    pub async fn recv(&mut self) {
+        // An error means the coordinator is gone - which is as good as
+        // a shutdown request.
+        let _ = self.receiver.wait_for(|shutdown| *shutdown).await;
+    }
+}
+
+/// Spawns tasks, tells them all when it's time to stop, and waits for
+/// them to finish.
+pub struct ShutdownCoordinator {
+    sender: watch::Sender<bool>,
+    tasks: JoinSet<()>,
+    running: Arc<Mutex<Vec<&'static str>>>,
+}
+
+// Removes a task's name from the running list when the task finishes -
+// whether it returned normally or panicked.
+struct RunningGuard {
+    name: &'static str,
+    running: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        let mut running = self.running.lock().unwrap();
+        if let Some(index) = running.iter().position(|n| *n == self.name) {
+            running.remove(index);
+        }
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender,
+            tasks: JoinSet::new(),
+            running: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, task: F)
+    where
+        F: FnOnce(Shutdown) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let shutdown = Shutdown {
+            receiver: self.sender.subscribe(),
+        };
+        let future = task(shutdown);
+        self.running.lock().unwrap().push(name);
+        let guard = RunningGuard {
+            name,
+            running: self.running.clone(),
+        };
+        self.tasks.spawn(async move {
+            let _guard = guard;
+            future.await;
+        });
+    }
+
+    /// Signals every task to stop, and waits up to `timeout` for them
+    /// to finish. Tasks that are still running after that are aborted,
+    /// and their names returned.
+    pub async fn shutdown(mut self, timeout: Duration) -> Vec<&'static str> {
+        // `send_replace` works even if every receiver has been dropped.
+        self.sender.send_replace(true);
+
+        let drain = async {
+            while let Some(result) = self.tasks.join_next().await {
+                if let Err(e) = result {
+                    println!("A task failed during shutdown: {e}");
+                }
+            }
+        };
+        if tokio::time::timeout(timeout, drain).await.is_ok() {
+            return Vec::new();
+        }
+
+        let stragglers = self.running.lock().unwrap().clone();
+        self.tasks.abort_all();
+        // Wait for the aborted tasks, so they've dropped everything
+        // they own (such as channel senders) before we return.
+        while self.tasks.join_next().await.is_some() {}
+        stragglers
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
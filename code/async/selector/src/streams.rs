@@ -0,0 +1,182 @@
+//! Stream operators, built on top of channels.
+//!
+//! `main` multiplexes channels by hand with `tokio::select!`. Turning
+//! each channel into a `Stream` lets you describe the same thing as a
+//! pipeline instead: merge these, throttle that, batch the result.
+//!
+//! Each operator is written with `async_stream::stream!`, so you can
+//! read it as an ordinary async loop that `yield`s its output.
+use async_stream::stream;
+use std::fmt;
+use std::pin::pin;
+use std::time::Duration;
+use tokio::sync::mpsc::Receiver;
+use tokio::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Turns an `mpsc` receiver into a `Stream`. The stream ends when
+/// every sender has been dropped.
+pub fn from_channel<T>(rx: Receiver<T>) -> ReceiverStream<T> {
+    ReceiverStream::new(rx)
+}
+
+/// Interleaves two streams, yielding items as soon as either one
+/// produces them. Ends when both streams have ended.
+pub fn merge<T, A, B>(a: A, b: B) -> impl Stream<Item = T>
+where
+    A: Stream<Item = T>,
+    B: Stream<Item = T>,
+{
+    stream! {
+        let mut a = pin!(a);
+        let mut b = pin!(b);
+        let mut a_done = false;
+        let mut b_done = false;
+        loop {
+            // `yield` can't be used inside `select!`, so we pick the
+            // item first and yield it afterwards.
+            let item = tokio::select! {
+                item = a.next(), if !a_done => {
+                    a_done = item.is_none();
+                    item
+                }
+                item = b.next(), if !b_done => {
+                    b_done = item.is_none();
+                    item
+                }
+                else => break,
+            };
+            if let Some(item) = item {
+                yield item;
+            }
+        }
+    }
+}
+
+/// Rate-limits a stream to one item per `period`. Nothing is dropped:
+/// items that arrive too quickly are delayed.
+pub fn throttle<S: Stream>(stream: S, period: Duration) -> impl Stream<Item = S::Item> {
+    stream! {
+        let mut stream = pin!(stream);
+        let mut next_allowed = Instant::now();
+        while let Some(item) = stream.next().await {
+            tokio::time::sleep_until(next_allowed).await;
+            next_allowed = Instant::now() + period;
+            yield item;
+        }
+    }
+}
+
+/// Waits for the stream to go quiet for `quiet`, then yields the most
+/// recent item. Anything superseded in the meantime is dropped.
+pub fn debounce<S: Stream>(stream: S, quiet: Duration) -> impl Stream<Item = S::Item> {
+    stream! {
+        let mut stream = pin!(stream);
+        let mut pending = None;
+        let mut timer = pin!(tokio::time::sleep(quiet));
+        loop {
+            let event = tokio::select! {
+                item = stream.next() => match item {
+                    Some(item) => Event::Item(item),
+                    None => Event::End,
+                },
+                _ = &mut timer, if pending.is_some() => Event::Timer,
+            };
+            match event {
+                Event::Item(item) => {
+                    pending = Some(item);
+                    timer.as_mut().reset(Instant::now() + quiet);
+                }
+                Event::Timer => {
+                    if let Some(item) = pending.take() {
+                        yield item;
+                    }
+                }
+                Event::End => {
+                    if let Some(item) = pending.take() {
+                        yield item;
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Groups items into batches. A batch is yielded when it holds
+/// `max_size` items, or `max_wait` after its first item arrived -
+/// whichever comes first. A partial batch is flushed when the stream
+/// ends.
+pub fn batch<S: Stream>(stream: S, max_size: usize, max_wait: Duration) -> impl Stream<Item = Vec<S::Item>> {
+    let max_size = max_size.max(1);
+    stream! {
+        let mut stream = pin!(stream);
+        let mut batch = Vec::with_capacity(max_size);
+        let mut deadline = pin!(tokio::time::sleep(max_wait));
+        loop {
+            let event = tokio::select! {
+                item = stream.next() => match item {
+                    Some(item) => Event::Item(item),
+                    None => Event::End,
+                },
+                _ = &mut deadline, if !batch.is_empty() => Event::Timer,
+            };
+            match event {
+                Event::Item(item) => {
+                    if batch.is_empty() {
+                        deadline.as_mut().reset(Instant::now() + max_wait);
+                    }
+                    batch.push(item);
+                    if batch.len() >= max_size {
+                        yield std::mem::replace(&mut batch, Vec::with_capacity(max_size));
+                    }
+                }
+                Event::Timer => {
+                    yield std::mem::replace(&mut batch, Vec::with_capacity(max_size));
+                }
+                Event::End => {
+                    if !batch.is_empty() {
+                        yield batch;
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Returned by `timeout_each` when an item took too long to arrive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out waiting for the next item")
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Gives every item `limit` to arrive. If it doesn't, yields
+/// `Err(TimedOut)` and keeps waiting - so a slow stream produces a
+/// series of timeouts rather than ending.
+pub fn timeout_each<S: Stream>(stream: S, limit: Duration) -> impl Stream<Item = Result<S::Item, TimedOut>> {
+    stream! {
+        let mut stream = pin!(stream);
+        loop {
+            match tokio::time::timeout(limit, stream.next()).await {
+                Ok(Some(item)) => yield Ok(item),
+                Ok(None) => break,
+                Err(_) => yield Err(TimedOut),
+            }
+        }
+    }
+}
+
+// What woke up an operator that is waiting on both its input and a timer.
+enum Event<T> {
+    Item(T),
+    Timer,
+    End,
+}
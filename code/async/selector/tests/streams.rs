@@ -0,0 +1,147 @@
+// Every test runs with Tokio's clock paused. Sleeps complete instantly
+// (the clock jumps straight to the next timer), so the timings below
+// are exact and the tests run in milliseconds.
+use async_stream::stream;
+use selector::streams::{batch, debounce, from_channel, merge, throttle, timeout_each, TimedOut};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_stream::{Stream, StreamExt};
+
+// Yields each `(millis, value)` pair at that offset from the start.
+fn timed<T>(items: Vec<(u64, T)>) -> impl Stream<Item = T> {
+    let start = Instant::now();
+    stream! {
+        for (millis, item) in items {
+            tokio::time::sleep_until(start + Duration::from_millis(millis)).await;
+            yield item;
+        }
+    }
+}
+
+// Collects a stream, recording when (in ms since the start) each item
+// came out of it.
+async fn collect_timed<S: Stream>(stream: S) -> Vec<(u64, S::Item)> {
+    let start = Instant::now();
+    let mut stream = std::pin::pin!(stream);
+    let mut result = Vec::new();
+    while let Some(item) = stream.next().await {
+        result.push((start.elapsed().as_millis() as u64, item));
+    }
+    result
+}
+
+#[tokio::test(start_paused = true)]
+async fn from_channel_ends_when_senders_drop() {
+    let (tx, rx) = tokio::sync::mpsc::channel(10);
+    tokio::spawn(async move {
+        for i in 0..3 {
+            tx.send(i).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let items: Vec<i32> = from_channel(rx).collect().await;
+    assert_eq!(items, vec![0, 1, 2]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn merge_interleaves_by_arrival() {
+    let a = timed(vec![(0, "a1"), (20, "a2"), (40, "a3")]);
+    let b = timed(vec![(10, "b1"), (30, "b2")]);
+
+    let items = collect_timed(merge(a, b)).await;
+    assert_eq!(
+        items,
+        vec![(0, "a1"), (10, "b1"), (20, "a2"), (30, "b2"), (40, "a3")]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn merge_continues_after_one_side_ends() {
+    let a = timed(vec![(0, 1)]);
+    let b = timed(vec![(10, 2), (50, 3)]);
+
+    let items = collect_timed(merge(a, b)).await;
+    assert_eq!(items, vec![(0, 1), (10, 2), (50, 3)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn throttle_spaces_out_a_burst() {
+    let burst = timed(vec![(0, 1), (0, 2), (0, 3), (0, 4)]);
+
+    let items = collect_timed(throttle(burst, Duration::from_millis(100))).await;
+    assert_eq!(items, vec![(0, 1), (100, 2), (200, 3), (300, 4)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn throttle_does_not_delay_slow_streams() {
+    let slow = timed(vec![(0, 1), (150, 2), (400, 3)]);
+
+    let items = collect_timed(throttle(slow, Duration::from_millis(100))).await;
+    assert_eq!(items, vec![(0, 1), (150, 2), (400, 3)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn debounce_keeps_the_last_item_of_each_burst() {
+    let bursts = timed(vec![
+        (0, 1),
+        (10, 2),
+        (20, 3),
+        // Quiet for 100ms, then another burst.
+        (120, 4),
+        (130, 5),
+        // A straggler, so that the stream doesn't end (and flush) early.
+        (400, 6),
+    ]);
+
+    let items = collect_timed(debounce(bursts, Duration::from_millis(50))).await;
+    assert_eq!(items, vec![(70, 3), (180, 5), (400, 6)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn debounce_flushes_when_the_stream_ends() {
+    let (tx, rx) = tokio::sync::mpsc::channel(10);
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+    drop(tx);
+
+    let items = collect_timed(debounce(from_channel(rx), Duration::from_millis(50))).await;
+    assert_eq!(items, vec![(0, 2)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn batch_yields_full_batches_immediately() {
+    let burst = timed((0..7).map(|i| (0, i)).collect());
+
+    let items = collect_timed(batch(burst, 3, Duration::from_millis(100))).await;
+    assert_eq!(
+        items,
+        vec![(0, vec![0, 1, 2]), (0, vec![3, 4, 5]), (0, vec![6])]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn batch_yields_partial_batches_after_max_wait() {
+    let slow = timed(vec![(0, 1), (40, 2), (120, 3), (300, 4)]);
+
+    let items = collect_timed(batch(slow, 10, Duration::from_millis(100))).await;
+    // The first batch's clock starts at 0ms, the second at 120ms. The
+    // last one is flushed when the stream ends.
+    assert_eq!(items, vec![(100, vec![1, 2]), (220, vec![3]), (300, vec![4])]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn timeout_each_reports_late_items_and_keeps_going() {
+    let slow = timed(vec![(10, 1), (100, 2), (120, 3)]);
+
+    let items = collect_timed(timeout_each(slow, Duration::from_millis(50))).await;
+    assert_eq!(
+        items,
+        vec![
+            (10, Ok(1)),
+            (60, Err(TimedOut)),
+            (100, Ok(2)),
+            (120, Ok(3)),
+        ]
+    );
+}
@@ -0,0 +1,162 @@
+//! A watchdog that notices when something blocks the async runtime.
+//!
+//! A heartbeat task sleeps for a fixed interval and measures how late
+//! it wakes up. If a task blocks the executor thread (with
+//! `std::thread::sleep`, or too much CPU work) the heartbeat can't run
+//! at all - so a plain OS thread keeps an eye on it, and reports which
+//! tracked task was running when the heartbeat stopped.
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+struct Shared {
+    interval: Duration,
+    threshold: Duration,
+    last_beat: Mutex<Instant>,
+    // Which tracked task each thread is currently polling.
+    running: Mutex<HashMap<ThreadId, &'static str>>,
+    stalled: AtomicBool,
+    stalls: AtomicUsize,
+    max_lag: Mutex<Duration>,
+    stop: AtomicBool,
+}
+
+impl Shared {
+    fn running_tasks(&self) -> String {
+        let running = self.running.lock().unwrap();
+        if running.is_empty() {
+            "an untracked task".to_string()
+        } else {
+            let mut names: Vec<&str> = running.values().copied().collect();
+            names.sort();
+            names.join(", ")
+        }
+    }
+}
+
+/// Watches a Tokio runtime for scheduling stalls.
+///
+/// Dropping the `Watchdog` stops the heartbeat and the monitor thread.
+pub struct Watchdog {
+    shared: Arc<Shared>,
+}
+
+impl Watchdog {
+    /// Starts the heartbeat on the current runtime, and a monitor
+    /// thread. A stall is reported when the heartbeat is more than
+    /// `threshold` late. Must be called from inside a runtime.
+    pub fn start(interval: Duration, threshold: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            interval,
+            threshold,
+            last_beat: Mutex::new(Instant::now()),
+            running: Mutex::new(HashMap::new()),
+            stalled: AtomicBool::new(false),
+            stalls: AtomicUsize::new(0),
+            max_lag: Mutex::new(Duration::ZERO),
+            stop: AtomicBool::new(false),
+        });
+
+        tokio::spawn(heartbeat(shared.clone()));
+
+        let monitor = shared.clone();
+        std::thread::spawn(move || monitor_thread(monitor));
+
+        Self { shared }
+    }
+
+    /// Wraps a future so that the watchdog can name it if it stalls
+    /// the runtime.
+    pub fn track<F: Future>(&self, name: &'static str, future: F) -> Tracked<F> {
+        Tracked {
+            name,
+            inner: Box::pin(future),
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// How many stalls have been detected so far.
+    pub fn stalls(&self) -> usize {
+        self.shared.stalls.load(Relaxed)
+    }
+
+    /// The worst scheduling lag the heartbeat has seen.
+    pub fn max_lag(&self) -> Duration {
+        *self.shared.max_lag.lock().unwrap()
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Relaxed);
+    }
+}
+
+async fn heartbeat(shared: Arc<Shared>) {
+    while !shared.stop.load(Relaxed) {
+        let expected = Instant::now() + shared.interval;
+        tokio::time::sleep(shared.interval).await;
+        let now = Instant::now();
+        let lag = now.saturating_duration_since(expected);
+
+        *shared.last_beat.lock().unwrap() = now;
+        {
+            let mut max_lag = shared.max_lag.lock().unwrap();
+            *max_lag = (*max_lag).max(lag);
+        }
+        if shared.stalled.swap(false, Relaxed) {
+            println!(
+                "Watchdog: runtime recovered, heartbeat was {:.1}ms late",
+                lag.as_secs_f32() * 1000.0
+            );
+        }
+    }
+}
+
+fn monitor_thread(shared: Arc<Shared>) {
+    while !shared.stop.load(Relaxed) {
+        std::thread::sleep(shared.interval / 2);
+
+        let since_beat = shared.last_beat.lock().unwrap().elapsed();
+        let lag = since_beat.saturating_sub(shared.interval);
+        if lag > shared.threshold && !shared.stalled.swap(true, Relaxed) {
+            shared.stalls.fetch_add(1, Relaxed);
+            println!(
+                "Watchdog: runtime stalled, heartbeat is {:.1}ms late while running {}",
+                lag.as_secs_f32() * 1000.0,
+                shared.running_tasks()
+            );
+        }
+    }
+}
+
+/// A future wrapped by `Watchdog::track`. It records its name while
+/// it is being polled.
+pub struct Tracked<F> {
+    name: &'static str,
+    inner: Pin<Box<F>>,
+    shared: Arc<Shared>,
+}
+
+impl<F: Future> Future for Tracked<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let thread = std::thread::current().id();
+        let previous = self.shared.running.lock().unwrap().insert(thread, self.name);
+        let result = self.inner.as_mut().poll(cx);
+
+        let mut running = self.shared.running.lock().unwrap();
+        match previous {
+            // We were polled from inside another tracked future.
+            Some(outer) => running.insert(thread, outer),
+            None => running.remove(&thread),
+        };
+        result
+    }
+}
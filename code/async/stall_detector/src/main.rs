@@ -0,0 +1,47 @@
+use stall_detector::Watchdog;
+use std::time::Duration;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let watchdog = Watchdog::start(Duration::from_millis(1), Duration::from_millis(5));
+
+    println!("thread_sleep::correct_counter - this should be fine");
+    let mut handles = Vec::new();
+    for i in 5..10 {
+        let task = watchdog.track("correct_counter", thread_sleep::correct_counter(i as f32 / 10.0));
+        handles.push(tokio::spawn(task));
+    }
+    for handle in handles {
+        let _ = handle.await;
+    }
+
+    println!("thread_sleep::counter - blocks the runtime with std::thread::sleep");
+    let mut handles = Vec::new();
+    for i in 5..10 {
+        let task = watchdog.track("counter", thread_sleep::counter(i as f32 / 10.0));
+        handles.push(tokio::spawn(task));
+    }
+    for handle in handles {
+        let _ = handle.await;
+    }
+
+    // Let the heartbeat catch up, so the next stall is reported separately.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // One call is quick in a release build, so we make a few in a row.
+    println!("too_much_work::find_prime - hogs the CPU");
+    let find_primes = async {
+        for _ in 0..5 {
+            too_much_work::find_prime().await;
+        }
+    };
+    watchdog.track("find_prime", find_primes).await;
+
+    // Give the heartbeat a chance to notice that we've recovered.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    println!(
+        "Detected {} stalls, worst heartbeat lag {:.1}ms",
+        watchdog.stalls(),
+        watchdog.max_lag().as_secs_f32() * 1000.0
+    );
+}
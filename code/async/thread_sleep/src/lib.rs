@@ -0,0 +1,11 @@
+use std::time::Duration;
+
+pub async fn counter(n: f32) {
+    std::thread::sleep(Duration::from_secs_f32(1.0 - n));
+    println!("{n}");
+}
+
+pub async fn correct_counter(n: f32) {
+    tokio::time::sleep(Duration::from_secs_f32(1.0 - n)).await;
+    println!("{n}");
+}
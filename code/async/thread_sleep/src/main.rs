@@ -1,14 +1,5 @@
 use std::time::Duration;
-
-async fn counter(n: f32) {
-    std::thread::sleep(Duration::from_secs_f32(1.0 - n));
-    println!("{n}");
-}
-
-async fn correct_counter(n: f32) {
-    tokio::time::sleep(Duration::from_secs_f32(1.0 - n)).await;
-    println!("{n}");
-}
+use thread_sleep::correct_counter;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
@@ -17,4 +8,4 @@ async fn main() {
     }
 
     tokio::time::sleep(Duration::from_secs(1)).await;
-}
\ No newline at end of file
+}
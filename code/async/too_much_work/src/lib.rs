@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+pub fn is_prime(n: usize) -> bool {
+    if n <= 1 {
+        false
+    } else {
+        for div in 2 .. n {
+            if n.is_multiple_of(div) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+pub async fn find_prime() {
+    let result = is_prime(999983);
+    println!("Is prime: {}", result);
+}
+
+pub async fn spin() {
+    for _ in 0 .. 5 {
+        println!("Doing some work");
+        tokio::time::sleep(Duration::from_secs_f32(0.01)).await;
+    }
+}
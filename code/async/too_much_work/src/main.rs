@@ -1,30 +1,4 @@
-use std::time::Duration;
-
-fn is_prime(n: usize) -> bool {
-    if n <= 1 {
-        false
-    } else {
-        for div in 2 .. n {
-            if n % div == 0 {
-                return false;
-            }
-        }
-        true
-    }
-}
-
-async fn find_prime() {
-    let result = is_prime(999983);
-    println!("Is prime: {}", result);
-}
-
-async fn spin() {
-    for _ in 0 .. 5 {
-        println!("Doing some work");
-        tokio::time::sleep(Duration::from_secs_f32(0.01)).await;
-    }
-
-}
+use too_much_work::{find_prime, spin};
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
@@ -0,0 +1,146 @@
+// Compares the ways of running CPU-bound work from inside a
+// single-threaded Tokio runtime. For each strategy we measure:
+//
+// * Throughput: how many candidates `is_prime` checked per second.
+// * Tick latency: how late a `spin()`-style task that sleeps for
+//   10ms wakes up. This is what the other tasks on the runtime feel.
+//
+// Run it in release mode, optionally passing the number of candidates:
+// cargo run --release -p too_much_work_yield --bin yield_bench -- 200
+use latency_stats::{millis, percentile};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use too_much_work_yield::{is_prime, is_prime_budgeted, is_prime_yield_every, Budget};
+
+const FIRST_CANDIDATE: usize = 999_000;
+const DEFAULT_CANDIDATES: usize = 200;
+const TICK: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, Debug)]
+enum Strategy {
+    NoYield,
+    YieldEvery,
+    Budgeted,
+    SpawnBlocking,
+    Rayon,
+}
+
+const STRATEGIES: [Strategy; 5] = [
+    Strategy::NoYield,
+    Strategy::YieldEvery,
+    Strategy::Budgeted,
+    Strategy::SpawnBlocking,
+    Strategy::Rayon,
+];
+
+async fn check(strategy: Strategy, n: usize, budget: &mut Budget) -> bool {
+    match strategy {
+        Strategy::NoYield => is_prime(n),
+        Strategy::YieldEvery => is_prime_yield_every(n).await,
+        Strategy::Budgeted => is_prime_budgeted(n, budget).await,
+        Strategy::SpawnBlocking => tokio::task::spawn_blocking(move || is_prime(n))
+            .await
+            .unwrap(),
+        Strategy::Rayon => {
+            // Rayon doesn't know about async, so we hand the answer
+            // back through a oneshot channel.
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            rayon::spawn(move || {
+                let _ = tx.send(is_prime(n));
+            });
+            rx.await.unwrap()
+        }
+    }
+}
+
+// A `spin()` that never stops until told to, and remembers how late
+// each of its wake-ups was.
+async fn ticker(done: Arc<AtomicBool>) -> Vec<Duration> {
+    let mut lateness = Vec::new();
+    while !done.load(Relaxed) {
+        let start = Instant::now();
+        tokio::time::sleep(TICK).await;
+        lateness.push(start.elapsed().saturating_sub(TICK));
+    }
+    lateness
+}
+
+struct Report {
+    strategy: Strategy,
+    primes: usize,
+    checked: usize,
+    elapsed: Duration,
+    lateness: Vec<Duration>,
+}
+
+fn run(strategy: Strategy, candidates: Range<usize>) -> Report {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async move {
+        let done = Arc::new(AtomicBool::new(false));
+        let ticks = tokio::spawn(ticker(done.clone()));
+        // Let the ticker start its first sleep before we get busy.
+        tokio::task::yield_now().await;
+
+        let checked = candidates.len();
+        let mut budget = Budget::default();
+        let start = Instant::now();
+        let mut primes = 0;
+        for n in candidates {
+            if check(strategy, n, &mut budget).await {
+                primes += 1;
+            }
+        }
+        let elapsed = start.elapsed();
+
+        done.store(true, Relaxed);
+        let mut lateness = ticks.await.unwrap();
+        lateness.sort();
+
+        Report {
+            strategy,
+            primes,
+            checked,
+            elapsed,
+            lateness,
+        }
+    })
+}
+
+fn main() {
+    let count = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("The candidate count must be a number"))
+        .unwrap_or(DEFAULT_CANDIDATES);
+    let candidates = FIRST_CANDIDATE..FIRST_CANDIDATE + count;
+
+    println!(
+        "Checking {count} candidates from {FIRST_CANDIDATE}, with a {}ms ticker",
+        TICK.as_millis()
+    );
+    println!(
+        "{:<14} {:>7} {:>10} {:>10} {:>6} {:>10} {:>10} {:>10}",
+        "Strategy", "Primes", "Time (s)", "Checks/s", "Ticks", "p50 (ms)", "p99 (ms)", "max (ms)"
+    );
+    for strategy in STRATEGIES {
+        let report = run(strategy, candidates.clone());
+        let seconds = report.elapsed.as_secs_f64();
+        println!(
+            "{:<14} {:>7} {:>10.4} {:>10.0} {:>6} {:>10.3} {:>10.3} {:>10.3}",
+            format!("{:?}", report.strategy),
+            report.primes,
+            seconds,
+            report.checked as f64 / seconds,
+            report.lateness.len(),
+            millis(percentile(&report.lateness, 50.0)),
+            millis(percentile(&report.lateness, 99.0)),
+            millis(report.lateness.last().copied().unwrap_or_default()),
+        );
+    }
+    println!("Tick columns show how late the ticker woke up.");
+}
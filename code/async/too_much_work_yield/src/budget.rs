@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+// Reading the clock isn't free - it costs more than a loop iteration
+// of `is_prime`. So we only look at it every few hundred ticks.
+const CLOCK_CHECK_INTERVAL: u32 = 256;
+
+/// A cooperative scheduling budget.
+///
+/// Call `tick().await` once per unit of work. Every `iterations` ticks,
+/// or once `interval` of wall time has passed since the last yield
+/// (whichever comes first), it yields back to the runtime so other
+/// tasks get a turn. The rest of the time it returns immediately.
+pub struct Budget {
+    iterations: u32,
+    interval: Duration,
+    ticks: u32,
+    last_yield: Instant,
+}
+
+impl Budget {
+    pub fn new(iterations: u32, interval: Duration) -> Self {
+        Self {
+            iterations: iterations.max(1),
+            interval,
+            ticks: 0,
+            last_yield: Instant::now(),
+        }
+    }
+
+    /// Only yield based on the number of iterations.
+    pub fn every_iterations(iterations: u32) -> Self {
+        Self::new(iterations, Duration::MAX)
+    }
+
+    /// Only yield based on elapsed wall time.
+    pub fn every_interval(interval: Duration) -> Self {
+        Self::new(u32::MAX, interval)
+    }
+
+    pub async fn tick(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+        let out_of_iterations = self.ticks >= self.iterations;
+        let out_of_time = self.ticks.is_multiple_of(CLOCK_CHECK_INTERVAL)
+            && self.last_yield.elapsed() >= self.interval;
+
+        if out_of_iterations || out_of_time {
+            tokio::task::yield_now().await;
+            self.ticks = 0;
+            self.last_yield = Instant::now();
+        }
+    }
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::new(10_000, Duration::from_micros(500))
+    }
+}
@@ -0,0 +1,49 @@
+mod budget;
+
+pub use budget::Budget;
+
+/// The original, blocking version. It never gives the runtime a
+/// chance to run anything else.
+pub fn is_prime(n: usize) -> bool {
+    if n <= 1 {
+        false
+    } else {
+        for div in 2 .. n {
+            if n.is_multiple_of(div) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Yields on every divisor. Other tasks run smoothly, but the
+/// yield costs far more than the work it interrupts.
+pub async fn is_prime_yield_every(n: usize) -> bool {
+    if n <= 1 {
+        false
+    } else {
+        for div in 2 .. n {
+            if n.is_multiple_of(div) {
+                return false;
+            }
+            tokio::task::yield_now().await;
+        }
+        true
+    }
+}
+
+/// Only yields when the `Budget` says we've done enough work.
+pub async fn is_prime_budgeted(n: usize, budget: &mut Budget) -> bool {
+    if n <= 1 {
+        false
+    } else {
+        for div in 2 .. n {
+            if n.is_multiple_of(div) {
+                return false;
+            }
+            budget.tick().await;
+        }
+        true
+    }
+}
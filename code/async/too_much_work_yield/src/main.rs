@@ -1,21 +1,12 @@
 use std::time::Duration;
-
-async fn is_prime(n: usize) -> bool {
-    if n <= 1 {
-        false
-    } else {
-        for div in 2 .. n {
-            if n % div == 0 {
-                return false;
-            }
-            tokio::task::yield_now().await;
-        }
-        true
-    }
-}
+use too_much_work_yield::{is_prime_budgeted, Budget};
 
 async fn find_prime() {
-    let result = is_prime(999983).await;
+    // Yield every 10,000 divisors or every half millisecond - whichever
+    // comes first. Compare with `is_prime_yield_every`, which yields on
+    // every single divisor.
+    let mut budget = Budget::new(10_000, Duration::from_micros(500));
+    let result = is_prime_budgeted(999983, &mut budget).await;
     println!("Is prime: {}", result);
 }
 
@@ -0,0 +1,69 @@
+// The Rust port of `cpp/atomic_thread/atomic.cpp`.
+//
+// Pass an ordering name (`relaxed`, `release`, `acquire`, `acqrel`,
+// `seqcst`) to test just that ordering. With no arguments, every
+// ordering is run so you can compare the timings.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const NUM_THREADS: usize = 3;
+const ITERATIONS: usize = 100_000;
+
+// Every ordering is valid for a read-modify-write operation like
+// `fetch_add`. They all give the right answer - what changes is
+// how much synchronization the CPU has to perform.
+const ORDERINGS: [(&str, Ordering); 5] = [
+    ("relaxed", Ordering::Relaxed),
+    ("release", Ordering::Release),
+    ("acquire", Ordering::Acquire),
+    ("acqrel", Ordering::AcqRel),
+    ("seqcst", Ordering::SeqCst),
+];
+
+fn parse_ordering(name: &str) -> Option<Ordering> {
+    ORDERINGS
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, ordering)| *ordering)
+}
+
+fn count(ordering: Ordering) {
+    let counter = AtomicUsize::new(0);
+
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..NUM_THREADS {
+            scope.spawn(|| {
+                for _i in 0..ITERATIONS {
+                    counter.fetch_add(1, ordering);
+                }
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+
+    // The scope joined every thread, which synchronizes with us -
+    // so a `Relaxed` load is guaranteed to see the final value.
+    println!("{ordering:?}");
+    println!("  Counter: {}", counter.load(Ordering::Relaxed));
+    println!("  Calculated in {:.4} seconds", elapsed.as_secs_f32());
+}
+
+fn main() {
+    let orderings: Vec<Ordering> = match std::env::args().nth(1) {
+        Some(name) => match parse_ordering(&name) {
+            Some(ordering) => vec![ordering],
+            None => {
+                eprintln!("Unknown ordering: {name}");
+                eprintln!("Expected one of: relaxed, release, acquire, acqrel, seqcst");
+                std::process::exit(1);
+            }
+        },
+        None => ORDERINGS.iter().map(|(_, ordering)| *ordering).collect(),
+    };
+
+    println!("Expected: {}", NUM_THREADS * ITERATIONS);
+    for ordering in orderings {
+        count(ordering);
+    }
+}
@@ -0,0 +1,37 @@
+// The Rust port of `cpp/mutex_spot/mutex_spot.cpp`.
+//
+// The C++ version forgets to take the lock in the second thread.
+// That can't happen here: the `Mutex` wraps the data, so the only
+// way to reach `my_safe_data` is through a lock guard.
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+const ITERATIONS: usize = 10_000;
+
+fn main() {
+    let my_safe_data: Mutex<i32> = Mutex::new(0);
+
+    let start = Instant::now();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for _i in 0..ITERATIONS {
+                let mut lock = my_safe_data.lock().unwrap();
+                *lock += 1;
+            }
+        });
+
+        scope.spawn(|| {
+            for _i in 0..ITERATIONS {
+                let mut lock = my_safe_data.lock().unwrap();
+                *lock += 1;
+            }
+        });
+    });
+    let elapsed = start.elapsed();
+
+    let lock = my_safe_data.lock().unwrap();
+    println!("{}", *lock);
+    println!("Expected: {}", ITERATIONS * 2);
+    println!("Calculated in {:.4} seconds", elapsed.as_secs_f32());
+}
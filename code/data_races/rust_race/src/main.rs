@@ -0,0 +1,76 @@
+// The Rust port of `cpp/break_thread/race.cpp`.
+//
+// Safe Rust won't let you write this bug: three threads mutably
+// borrowing `counter` is a compile error. To reproduce the C++
+// behavior you have to opt in to `unsafe` - and even then, only
+// if you build with `--features unsafe`.
+
+#[cfg(feature = "unsafe")]
+mod racy {
+    use std::cell::UnsafeCell;
+
+    // An `UnsafeCell` is the building block of every interior
+    // mutability type (`Mutex`, `RefCell`, our `SpinLock`). Used
+    // on its own, it provides no synchronization at all.
+    pub struct RacyCounter(UnsafeCell<usize>);
+
+    // Safety: it isn't. We're lying to the compiler so that we can
+    // share the counter between threads - which is exactly the bug
+    // we're demonstrating.
+    unsafe impl Sync for RacyCounter {}
+
+    impl RacyCounter {
+        pub const fn new() -> Self {
+            Self(UnsafeCell::new(0))
+        }
+
+        pub fn increment(&self) {
+            // Volatile reads and writes stop LLVM from folding the
+            // loop into a single `+= 100_000`, so we get the same
+            // load/add/store interleaving as the C++ version.
+            let ptr = self.0.get();
+            unsafe {
+                let value = ptr.read_volatile();
+                ptr.write_volatile(value + 1);
+            }
+        }
+
+        pub fn get(&self) -> usize {
+            unsafe { *self.0.get() }
+        }
+    }
+}
+
+const NUM_THREADS: usize = 3;
+const ITERATIONS: usize = 100_000;
+
+#[cfg(feature = "unsafe")]
+fn main() {
+    use std::time::Instant;
+
+    let counter = racy::RacyCounter::new();
+
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..NUM_THREADS {
+            scope.spawn(|| {
+                for _i in 0..ITERATIONS {
+                    counter.increment();
+                }
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+
+    println!("Counter: {}", counter.get());
+    println!("Expected: {}", NUM_THREADS * ITERATIONS);
+    println!("Calculated in {:.4} seconds", elapsed.as_secs_f32());
+}
+
+#[cfg(not(feature = "unsafe"))]
+fn main() {
+    println!(
+        "The racy counter ({NUM_THREADS} threads x {ITERATIONS} increments) is only built with the `unsafe` feature."
+    );
+    println!("Run it with: cargo run -p rust_race --features unsafe");
+}
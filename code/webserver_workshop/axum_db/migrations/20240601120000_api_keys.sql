@@ -0,0 +1,10 @@
+-- API keys for the person API. We only keep a SHA-256 hash of each key,
+-- so a leaked database doesn't leak working keys.
+CREATE TABLE api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    -- Space-separated, like OAuth: "read", "write" or "read write".
+    scopes TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
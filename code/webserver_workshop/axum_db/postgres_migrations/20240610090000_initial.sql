@@ -0,0 +1,11 @@
+-- The same `my_data` table as `migrations/20240529173000_initial.sql`, for
+-- `--people-url postgres://...`. Only the people live in Postgres; API
+-- keys stay in SQLite, so there's no `api_keys` table here.
+CREATE TABLE my_data (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    age INTEGER NOT NULL
+);
+
+INSERT INTO my_data (name, age) VALUES ('Alice', 42);
+INSERT INTO my_data (name, age) VALUES ('Bob', 69);
@@ -0,0 +1,198 @@
+use crate::db;
+use crate::error::AppError;
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header;
+use axum::http::request::Parts;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::fmt;
+
+// Clients send an API key as a bearer token:
+//
+//   Authorization: Bearer 3f9a...
+//
+// Handlers say what they need by taking a `Reader` or `Writer` argument.
+// If the key is missing or unknown that's a 401; if it's known but lacks
+// the scope, a 403.
+
+/// What an API key is allowed to do.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    /// Look people up.
+    Read,
+    /// Add, change and delete people.
+    Write,
+}
+
+impl Scope {
+    fn as_str(self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+        }
+    }
+
+    fn parse(scope: &str) -> Option<Self> {
+        match scope {
+            "read" => Some(Scope::Read),
+            "write" => Some(Scope::Write),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A known API key, and what it may do.
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+}
+
+impl ApiKey {
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// A new, random key: 32 bytes, hex encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// The keys are long and random, so a plain SHA-256 is enough. Passwords
+// would need a slow hash (argon2, bcrypt) to resist guessing.
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Stores (the hash of) `token` as a key called `name`.
+pub async fn add_api_key(
+    pool: &SqlitePool,
+    name: &str,
+    token: &str,
+    scopes: &[Scope],
+) -> Result<(), sqlx::Error> {
+    let scopes: Vec<&str> = scopes.iter().map(|scope| scope.as_str()).collect();
+    let query = sqlx::query("INSERT INTO api_keys (name, key_hash, scopes) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(hash_token(token))
+        .bind(scopes.join(" "));
+    db::timed("insert api key", query.execute(pool)).await?;
+    Ok(())
+}
+
+/// Generates and stores a new key. The token is returned, and this is
+/// the only time anyone gets to see it.
+pub async fn create_api_key(
+    pool: &SqlitePool,
+    name: &str,
+    scopes: &[Scope],
+) -> Result<String, sqlx::Error> {
+    let token = generate_token();
+    add_api_key(pool, name, &token, scopes).await?;
+    Ok(token)
+}
+
+/// What `--create-api-key` does: creates the key, and prints it (on its
+/// own line, so scripts can pick it out) for whoever ran the server.
+pub async fn create_and_print_api_key(
+    pool: &SqlitePool,
+    name: &str,
+    scopes: &[Scope],
+) -> Result<(), sqlx::Error> {
+    let token = create_api_key(pool, name, scopes).await?;
+    println!(
+        "Created API key `{name}`. Send it as `Authorization: Bearer <key>` - it won't be shown again:"
+    );
+    println!("{token}");
+    Ok(())
+}
+
+async fn authenticate(parts: &Parts, pool: &SqlitePool) -> Result<ApiKey, AppError> {
+    let header = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| {
+            AppError::Unauthorized("missing `Authorization: Bearer <key>` header".to_string())
+        })?;
+    let token = match header.split_once(' ') {
+        // The scheme is case-insensitive.
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => token.trim(),
+        _ => {
+            return Err(AppError::Unauthorized(
+                "expected a bearer token".to_string(),
+            ))
+        }
+    };
+
+    let query = sqlx::query_as("SELECT name, scopes FROM api_keys WHERE key_hash = ?")
+        .bind(hash_token(token));
+    let row: Option<(String, String)> =
+        db::timed("select api key", query.fetch_optional(pool)).await?;
+    let (name, scopes) =
+        row.ok_or_else(|| AppError::Unauthorized("unknown API key".to_string()))?;
+    Ok(ApiKey {
+        name,
+        scopes: scopes.split_whitespace().filter_map(Scope::parse).collect(),
+    })
+}
+
+async fn authorize(parts: &Parts, pool: &SqlitePool, scope: Scope) -> Result<ApiKey, AppError> {
+    let key = authenticate(parts, pool).await?;
+    if !key.allows(scope) {
+        return Err(AppError::Forbidden(format!(
+            "this API key doesn't have the `{scope}` scope"
+        )));
+    }
+    Ok(key)
+}
+
+/// Extracting this requires a key with the `read` scope.
+pub struct Reader(pub ApiKey);
+
+/// Extracting this requires a key with the `write` scope.
+pub struct Writer(pub ApiKey);
+
+// Both work with any state that has a `SqlitePool` in it - so
+// `axum_db_cache` can use them too.
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Reader
+where
+    SqlitePool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        authorize(parts, &SqlitePool::from_ref(state), Scope::Read)
+            .await
+            .map(Reader)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Writer
+where
+    SqlitePool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        authorize(parts, &SqlitePool::from_ref(state), Scope::Write)
+            .await
+            .map(Writer)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::config::DatabaseConfig;
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// Used when no database URL is configured.
+pub const DEFAULT_DATABASE_URL: &str = "sqlite://my_database.db";
+
+/// The contents of `migrations/`, compiled into the binary - so you
+/// don't need `sqlx-cli` to set up a database.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Opens a connection pool, creating the SQLite file if it doesn't
+/// exist yet.
+pub async fn connect(config: &DatabaseConfig) -> Result<SqlitePool, sqlx::Error> {
+    let journal_mode = if config.wal {
+        SqliteJournalMode::Wal
+    } else {
+        SqliteJournalMode::Delete
+    };
+    let options = SqliteConnectOptions::from_str(&config.url)?
+        .create_if_missing(true)
+        .busy_timeout(config.busy_timeout)
+        .journal_mode(journal_mode);
+    SqlitePoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .connect_with(options)
+        .await
+}
+
+/// Brings the database schema up to date. Migrations that have already
+/// run (including any applied with `sqlx migrate run`) are skipped.
+pub async fn migrate(pool: &SqlitePool) -> Result<(), sqlx::migrate::MigrateError> {
+    MIGRATOR.run(pool).await
+}
+
+/// Runs one query inside a `db.query` span, recording what it was and
+/// how long it took:
+///
+/// ```ignore
+/// let person = db::timed("select person", query.fetch_one(&pool)).await?;
+/// ```
+pub async fn timed<F: Future>(operation: &'static str, query: F) -> F::Output {
+    let span = tracing::info_span!("db.query", operation, elapsed_ms = tracing::field::Empty);
+    let start = Instant::now();
+    let output = query.instrument(span.clone()).await;
+    span.record("elapsed_ms", start.elapsed().as_secs_f64() * 1000.0);
+    output
+}
@@ -0,0 +1,232 @@
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Everything that can go wrong in a handler. Returning
+/// `Result<T, AppError>` means a failed query becomes an HTTP error
+/// response, instead of a panic and a dropped connection.
+///
+/// It's `Clone` (`sqlx::Error` isn't, hence the `Arc`) so that one
+/// failure can be handed to several waiting requests.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum AppError {
+    #[error("not found")]
+    NotFound,
+    #[error("{0}")]
+    BadRequest(String),
+    /// No API key, or one we don't know.
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+    /// A known API key, without the scope the route needs.
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+    /// This client has sent too many requests; it may try again after
+    /// the given time.
+    #[error("rate limited")]
+    RateLimited(Duration),
+    /// Too many requests in flight.
+    #[error("overloaded")]
+    Overloaded,
+    #[error("timed out")]
+    Timeout,
+    #[error("validation failed")]
+    Validation(Vec<FieldError>),
+    #[error("database error: {0}")]
+    Database(Arc<sqlx::Error>),
+    #[error("template error: {0}")]
+    Template(Arc<askama::Error>),
+}
+
+impl AppError {
+    /// `Ok` if there are no errors, otherwise a `Validation` error -
+    /// so handlers can finish a batch of checks with a `?`.
+    pub fn validation(errors: Vec<FieldError>) -> Result<(), AppError> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(errors))
+        }
+    }
+}
+
+/// One problem with one field of a request.
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+// We can't use `#[from]` here: a missing row isn't really a database
+// error, it's a 404.
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::RowNotFound => AppError::NotFound,
+            error => AppError::Database(Arc::new(error)),
+        }
+    }
+}
+
+impl From<askama::Error> for AppError {
+    fn from(error: askama::Error) -> Self {
+        AppError::Template(Arc::new(error))
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
+
+// A body that isn't JSON at all (or isn't labelled as JSON) is a 400. JSON
+// with missing or wrongly typed fields is a 422, like any other invalid
+// field - and that's what the OpenAPI document promises.
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::JsonDataError(_) => {
+                AppError::Validation(vec![FieldError::new("body", rejection.body_text())])
+            }
+            rejection => AppError::BadRequest(rejection.body_text()),
+        }
+    }
+}
+
+/// The JSON body of every error response. The fields follow RFC 7807
+/// ("Problem Details for HTTP APIs").
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub(crate) struct Problem {
+    r#type: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    /// Only for a `422`: what was wrong with each field.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, detail) = match &self {
+            AppError::Validation(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The request contained invalid fields".to_string(),
+            ),
+            AppError::NotFound => (
+                StatusCode::NOT_FOUND,
+                "The requested resource doesn't exist".to_string(),
+            ),
+            AppError::BadRequest(message) => (StatusCode::BAD_REQUEST, message.clone()),
+            AppError::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message.clone()),
+            AppError::Forbidden(message) => (StatusCode::FORBIDDEN, message.clone()),
+            AppError::RateLimited(_) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many requests - slow down".to_string(),
+            ),
+            AppError::Overloaded => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "The server is too busy right now".to_string(),
+            ),
+            AppError::Timeout => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "The request took too long".to_string(),
+            ),
+            AppError::Database(error) => {
+                // Log the details, but don't leak them to the client.
+                tracing::error!(%error, "database error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "An internal error occurred".to_string(),
+                )
+            }
+            AppError::Template(error) => {
+                tracing::error!(%error, "template error");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "An internal error occurred".to_string(),
+                )
+            }
+        };
+
+        // A 401 has to say how to authenticate.
+        let challenge = matches!(self, AppError::Unauthorized(_));
+        // Tell clients we've turned away when to come back (in whole
+        // seconds, rounded up).
+        let retry_after = match &self {
+            AppError::RateLimited(wait) => {
+                Some(wait.as_secs() + u64::from(wait.subsec_nanos() > 0))
+            }
+            AppError::Overloaded | AppError::Timeout => Some(1),
+            _ => None,
+        };
+        let problem = Problem {
+            r#type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            detail,
+            errors: match self {
+                AppError::Validation(errors) => errors,
+                _ => Vec::new(),
+            },
+        };
+        let mut response = (status, axum::Json(problem)).into_response();
+        response.headers_mut().insert(
+            axum::http::header::CONTENT_TYPE,
+            axum::http::HeaderValue::from_static("application/problem+json"),
+        );
+        if challenge {
+            response.headers_mut().insert(
+                axum::http::header::WWW_AUTHENTICATE,
+                axum::http::HeaderValue::from_static("Bearer"),
+            );
+        }
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(axum::http::header::RETRY_AFTER, seconds.max(1).into());
+        }
+        response
+    }
+}
+
+/// Axum's `Path` extractor, but a bad parameter becomes an `AppError`
+/// (and so a JSON 400) rather than a plain-text rejection.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct Path<T>(pub T);
+
+/// Likewise for `Query`.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct Query<T>(pub T);
+
+/// And for `Json`. Responses use it too, so it implements
+/// `IntoResponse` as well.
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct Json<T>(pub T);
+
+impl<T: serde::Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
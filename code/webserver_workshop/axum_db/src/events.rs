@@ -0,0 +1,224 @@
+use crate::auth::Reader;
+use crate::person::Person;
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{FromRef, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use futures_util::stream::{self, Stream};
+use sqlx::SqlitePool;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch};
+
+// The write handlers publish a `PersonEvent` after each change, and every
+// `/events` (Server-Sent Events) or `/ws` (WebSocket) client gets a copy.
+//
+// A `broadcast` channel keeps the last `capacity` events. A client that
+// falls further behind than that - a slow network, say - doesn't hold
+// everyone else up: it misses some events, and we tell it how many with a
+// `lagged` event. It should then re-read whatever it cares about.
+
+/// How many events a subscriber can fall behind before it misses some.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Something that happened to a person.
+#[derive(serde::Serialize, utoipa::ToSchema, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PersonEvent {
+    Created {
+        person: Person,
+    },
+    Updated {
+        person: Person,
+    },
+    Deleted {
+        id: i32,
+    },
+    /// Never published: it's what a subscriber gets instead of the
+    /// `missed` events it fell too far behind to see.
+    Lagged {
+        missed: u64,
+    },
+}
+
+impl PersonEvent {
+    /// The SSE event name - the same as `type` in the JSON.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PersonEvent::Created { .. } => "created",
+            PersonEvent::Updated { .. } => "updated",
+            PersonEvent::Deleted { .. } => "deleted",
+            PersonEvent::Lagged { .. } => "lagged",
+        }
+    }
+}
+
+/// The sending side, shared through `AppState`.
+#[derive(Clone)]
+pub struct Events {
+    sender: broadcast::Sender<PersonEvent>,
+    // Open streams would keep a graceful shutdown waiting forever, so
+    // `close` ends them all.
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl Events {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        let (closed, _) = watch::channel(false);
+        Self {
+            sender,
+            closed: Arc::new(closed),
+        }
+    }
+
+    /// Sends `event` to everyone listening right now. With nobody
+    /// listening, it goes nowhere - that's fine.
+    pub fn publish(&self, event: PersonEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Starts listening. Only events published from now on are seen.
+    pub fn subscribe(&self) -> Subscription {
+        Subscription {
+            receiver: self.sender.subscribe(),
+            closed: self.closed.subscribe(),
+        }
+    }
+
+    /// Ends every subscription, now and in future. `main` calls this when
+    /// it starts shutting down.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+pub struct Subscription {
+    receiver: broadcast::Receiver<PersonEvent>,
+    closed: watch::Receiver<bool>,
+}
+
+impl Subscription {
+    /// The next event, or `Lagged` if we fell behind. `None` once the
+    /// events are closed.
+    pub async fn next(&mut self) -> Option<PersonEvent> {
+        tokio::select! {
+            // Checked first, so a busy channel can't keep us going after
+            // a shutdown. (If every `Events` is dropped instead, this
+            // branch is disabled, and the channel runs dry by itself.)
+            biased;
+            Ok(_) = self.closed.wait_for(|closed| *closed) => None,
+            received = self.receiver.recv() => match received {
+                Ok(event) => Some(event),
+                // Receiving again carries on from the oldest event still
+                // in the channel.
+                Err(RecvError::Lagged(missed)) => Some(PersonEvent::Lagged { missed }),
+                Err(RecvError::Closed) => None,
+            },
+        }
+    }
+}
+
+/// `/events` and `/ws`, for any state that has the `Events` to stream
+/// (and the pool that `Reader` checks keys against) - so `axum_db_cache`
+/// can serve them too.
+pub fn routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    Events: FromRef<S>,
+    SqlitePool: FromRef<S>,
+{
+    Router::new()
+        .route("/events", get(server_sent_events))
+        .route("/ws", get(websocket))
+}
+
+/// Stream changes to people as Server-Sent Events.
+///
+/// Each event's name is its `type`, and its data is the whole event as
+/// JSON.
+#[utoipa::path(
+    get,
+    path = "/events",
+    responses(
+        (status = 200, body = PersonEvent, content_type = "text/event-stream"),
+        (status = 401, response = crate::openapi::Unauthorized),
+        (status = 403, response = crate::openapi::Forbidden),
+    ),
+    security(("api_key" = ["read"])),
+)]
+async fn server_sent_events(
+    _: Reader,
+    State(events): State<Events>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // Subscribe before we answer, so nothing published after the
+    // response starts is missed.
+    let subscription = events.subscribe();
+    let stream = stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next().await?;
+        let sse = Event::default()
+            .event(event.name())
+            .json_data(&event)
+            .expect("events serialize to JSON");
+        Some((Ok(sse), subscription))
+    });
+    // A comment every 15 seconds stops proxies closing a quiet stream.
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Stream changes to people over a WebSocket.
+///
+/// Each text message is one event, as JSON. Anything the client sends is
+/// ignored.
+#[utoipa::path(
+    get,
+    path = "/ws",
+    responses(
+        (status = 101, description = "Switching to a WebSocket of `PersonEvent`s"),
+        (status = 401, response = crate::openapi::Unauthorized),
+        (status = 403, response = crate::openapi::Forbidden),
+    ),
+    security(("api_key" = ["read"])),
+)]
+async fn websocket(_: Reader, State(events): State<Events>, upgrade: WebSocketUpgrade) -> Response {
+    let subscription = events.subscribe();
+    upgrade.on_upgrade(|socket| forward(socket, subscription))
+}
+
+async fn forward(mut socket: WebSocket, mut subscription: Subscription) {
+    loop {
+        tokio::select! {
+            event = subscription.next() => {
+                let Some(event) = event else { break };
+                let json = serde_json::to_string(&event).expect("events serialize to JSON");
+                if socket.send(Message::Text(json)).await.is_err() {
+                    // They've gone.
+                    return;
+                }
+            }
+            // We have to read, to notice the client closing (axum answers
+            // pings for us).
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    // We're shutting down - say so, rather than just dropping the
+    // connection.
+    let close = CloseFrame {
+        code: close_code::AWAY,
+        reason: "server shutting down".into(),
+    };
+    let _ = socket.send(Message::Close(Some(close))).await;
+}
@@ -0,0 +1,75 @@
+use axum::{middleware, routing::get, Router};
+
+pub mod auth;
+pub mod config;
+pub mod db;
+mod error;
+pub mod events;
+pub mod limits;
+pub mod metrics;
+pub mod openapi;
+mod pages;
+mod person;
+pub mod repository;
+pub mod shutdown;
+mod state;
+pub mod telemetry;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+pub use config::Config;
+pub use error::{AppError, FieldError, Json, Path, Query};
+pub use metrics::Metrics;
+pub use pages::render;
+pub use person::{NewPerson, Person, PersonPatch};
+pub use repository::PersonRepository;
+pub use state::AppState;
+
+/// Builds the application's router. `main` serves it; the tests call it
+/// directly, without binding a socket.
+pub fn app(state: AppState) -> Router {
+    let router = Router::new()
+        .route("/", get(say_hello))
+        .route("/json/:n", get(json_path))
+        .merge(pages::routes())
+        .merge(person::routes::<dyn PersonRepository>())
+        .merge(events::routes())
+        .merge(openapi::routes())
+        .route("/metrics", get(metrics::scrape));
+    // Inside the metrics layer, so requests we turn away are counted too.
+    let router = limits::apply(router, &state.config.limits)
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            metrics::track,
+        ))
+        .with_state(state);
+    telemetry::with_tracing(router)
+}
+
+async fn say_hello() -> &'static str {
+    "Hello, World!"
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct MyData {
+    name: String,
+    age: u32,
+}
+
+/// A made-up person, of whatever age you ask for.
+#[utoipa::path(
+    get,
+    path = "/json/{n}",
+    tag = "examples",
+    params(("n" = u32, Path, description = "Their age")),
+    responses(
+        (status = 200, body = MyData),
+        (status = 400, response = crate::openapi::BadRequest),
+    ),
+)]
+async fn json_path(Path(n): Path<u32>) -> Json<MyData> {
+    Json(MyData {
+        name: "Alice".to_string(),
+        age: n,
+    })
+}
@@ -0,0 +1,187 @@
+use crate::config::LimitsConfig;
+use crate::error::AppError;
+use axum::error_handling::HandleErrorLayer;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::{BoxError, Router};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower::load_shed::LoadShedLayer;
+use tower::timeout::TimeoutLayer;
+use tower::ServiceBuilder;
+
+/// Wraps every route in the limits that are turned on, from the outside
+/// in:
+///
+/// 1. A token bucket per client IP - too fast gets a 429.
+/// 2. A cap on requests in flight, across all clients - too many gets a
+///    503 straight away, rather than queueing for the database pool.
+/// 3. A time limit on each request - too slow gets a 504.
+///
+/// All three say when to try again with a `Retry-After` header.
+pub fn apply<S>(mut router: Router<S>, limits: &LimitsConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    // Each `layer` call wraps what's already there, so the innermost
+    // layer goes on first.
+    if !limits.request_timeout.is_zero() {
+        router = router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(timed_out))
+                .layer(TimeoutLayer::new(limits.request_timeout)),
+        );
+    }
+    if limits.max_concurrent > 0 {
+        // `Router::layer` wraps each route separately; the "global"
+        // version shares one semaphore between them all.
+        router = router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(overloaded))
+                .layer(LoadShedLayer::new())
+                .layer(GlobalConcurrencyLimitLayer::new(limits.max_concurrent)),
+        );
+    }
+    if limits.rate_per_sec > 0 {
+        let limiter = Arc::new(RateLimiter::new(limits.rate_per_sec, limits.burst));
+        router = router.layer(middleware::from_fn_with_state(limiter, rate_limit));
+    }
+    router
+}
+
+// tower reports "too slow" and "overloaded" as errors, but an axum route
+// has to answer every request - so we turn them back into responses.
+// (The routes themselves can't fail, so these are the only errors each
+// layer can produce.)
+async fn timed_out(_: BoxError) -> AppError {
+    AppError::Timeout
+}
+
+async fn overloaded(_: BoxError) -> AppError {
+    AppError::Overloaded
+}
+
+/// Per-client token buckets. Each client gets `burst` tokens to start
+/// with, and `rate_per_sec` more every second (up to `burst`). Each
+/// request costs one.
+pub struct RateLimiter {
+    rate_per_sec: f64,
+    burst: f64,
+    buckets: Mutex<Buckets>,
+}
+
+#[derive(Default)]
+struct Buckets {
+    // `None` is for requests that didn't come over a socket (the tests'
+    // `oneshot`) - they all share one bucket.
+    by_client: HashMap<Option<IpAddr>, Bucket>,
+    // Orders the clients from least to most recently seen, like the
+    // person cache's `recency`: every request files the client under a
+    // new, larger tick.
+    recency: BTreeMap<u64, Option<IpAddr>>,
+    tick: u64,
+}
+
+struct Bucket {
+    tokens: f64,
+    // `tokio`'s `Instant`, so a test can pause the clock and move it on.
+    updated: Instant,
+    // The `recency` key this bucket is filed under.
+    last_seen: u64,
+}
+
+/// The most clients we keep a bucket for. Without a limit, a flood of
+/// addresses would use up all our memory.
+pub const MAX_BUCKETS: usize = 10_000;
+
+impl RateLimiter {
+    pub fn new(rate_per_sec: u32, burst: u32) -> Self {
+        Self {
+            rate_per_sec: rate_per_sec as f64,
+            burst: burst as f64,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Takes a token from `client`'s bucket, or says how long until
+    /// there will be one.
+    pub fn check(&self, client: Option<IpAddr>) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if !buckets.by_client.contains_key(&client) {
+            self.make_room(&mut buckets, now);
+        }
+
+        buckets.tick += 1;
+        let tick = buckets.tick;
+        let bucket = buckets.by_client.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+            last_seen: 0,
+        });
+        let previous = std::mem::replace(&mut bucket.last_seen, tick);
+        bucket.tokens = self.refill(bucket, now);
+        bucket.updated = now;
+        let result = if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.rate_per_sec,
+            ))
+        };
+        buckets.recency.remove(&previous);
+        buckets.recency.insert(tick, client);
+        result
+    }
+
+    /// How many clients have a bucket right now.
+    pub fn clients(&self) -> usize {
+        self.buckets.lock().unwrap().by_client.len()
+    }
+
+    // Called before adding a client. At the limit, first forget the
+    // clients we haven't seen for long enough that their buckets have
+    // filled up again - a full bucket is the same as a new one. If they're
+    // all still busy, forget the one we saw longest ago anyway: it gets a
+    // full bucket if it comes back, which is kinder than running out of
+    // memory.
+    fn make_room(&self, buckets: &mut Buckets, now: Instant) {
+        if buckets.by_client.len() < MAX_BUCKETS {
+            return;
+        }
+        while let Some((&tick, client)) = buckets.recency.first_key_value() {
+            let full = self.refill(&buckets.by_client[client], now) >= self.burst;
+            if !full && buckets.by_client.len() < MAX_BUCKETS {
+                break;
+            }
+            let client = buckets.recency.remove(&tick).unwrap();
+            buckets.by_client.remove(&client);
+        }
+    }
+
+    fn refill(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.rate_per_sec).min(self.burst)
+    }
+}
+
+async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip());
+    match limiter.check(client) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => AppError::RateLimited(retry_after).into_response(),
+    }
+}
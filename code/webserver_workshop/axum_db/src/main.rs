@@ -1,68 +1,57 @@
-use axum::{extract::Path, response::Html, routing::get, Extension, Json, Router};
+use axum_db::config::{Config, ServerArgs};
+use axum_db::shutdown;
+use clap::Parser;
 
 #[tokio::main]
 async fn main() {
     // Run dotenvy
     let _ = dotenvy::dotenv(); // It's ok to not have a .env file
-    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let connection_pool = sqlx::SqlitePool::connect(&database_url)
-        .await.unwrap();
-
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3001").await.unwrap();
-
-    let app = Router::new()
-        .route("/", get(say_hello))
-        .route("/hello/:n", get(html_path))
-        .route("/json/:n", get(json_path))
-        .route("/person/:id", get(get_person))
-        .layer(Extension(connection_pool));
-
-    axum::serve(listener, app).await.unwrap();
-}
-
-async fn say_hello() -> &'static str {
-    "Hello, World!"
-}
-
-async fn html_path(
-    Path(n): Path<u32>,
-) -> Html<String> {
-    let base = include_str!("hello.html");
-    let templated = base.replace("$$MYPICK$$", &n.to_string());
-    Html(templated)
-}
-
-#[derive(serde::Serialize)]
-struct MyData {
-    name: String,
-    age: u32,
-}
-
-async fn json_path(
-    Path(n): Path<u32>,
-) -> axum::Json<MyData> {
-    axum::Json(MyData {
-        name: "Alice".to_string(),
-        age: n,
-    })
-}
-
-#[derive(serde::Serialize, sqlx::FromRow)]
-struct Person {
-    id: i32,
-    name: String,
-    age: i32,
-}
-
-async fn get_person(
-    Path(id): Path<i32>,
-    Extension(pool): Extension<sqlx::SqlitePool>,
-) -> Json<Person> {
-    let person = sqlx::query_as("SELECT * FROM my_data WHERE id = ?")
-        .bind(id)
-        .fetch_one(&pool)
+    let args = ServerArgs::parse();
+    let config = Config::load(&args).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        std::process::exit(2);
+    });
+    axum_db::telemetry::init(&config.log_filter, config.log_format);
+    println!("Effective configuration:\n{config}");
+
+    let connection_pool = axum_db::db::connect(&config.database).await.unwrap();
+
+    // Bring the schema up to date before we serve anything.
+    axum_db::db::migrate(&connection_pool).await.unwrap();
+    if args.migrate_only {
+        println!("Migrations applied to {}", config.database.url);
+        return;
+    }
+
+    if let Some(name) = &args.create_api_key {
+        axum_db::auth::create_and_print_api_key(&connection_pool, name, &args.scopes)
+            .await
+            .unwrap();
+        return;
+    }
+
+    let listener = tokio::net::TcpListener::bind(config.bind_address)
         .await
         .unwrap();
 
-    Json(person)
-}
\ No newline at end of file
+    let people = axum_db::repository::connect(&config.database, &connection_pool)
+        .await
+        .unwrap();
+    let state = axum_db::AppState {
+        people: people.clone(),
+        ..axum_db::AppState::with_config(connection_pool.clone(), config.clone())
+    };
+    let events = state.events.clone();
+    let app = axum_db::app(state);
+
+    // The event streams never finish by themselves, so we end them.
+    let signal = async move {
+        shutdown::signal().await;
+        events.close();
+    };
+    let drain = shutdown::serve(listener, app, signal, config.drain_timeout)
+        .await
+        .unwrap();
+    people.close(drain).await;
+    shutdown::close_pool(&connection_pool, drain).await;
+}
@@ -0,0 +1,137 @@
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use sqlx::SqlitePool;
+use std::time::Instant;
+
+/// The server's Prometheus metrics. Each `AppState` gets its own
+/// `Registry` (rather than using the global one), so tests running side
+/// by side don't count each other's requests.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    pool_connections: IntGauge,
+    pool_idle: IntGauge,
+    pool_max: IntGauge,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let labels = &["method", "route", "status"];
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            labels,
+        )
+        .unwrap();
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "How long requests took to handle",
+            ),
+            labels,
+        )
+        .unwrap();
+        let pool_connections = IntGauge::new(
+            "db_pool_connections",
+            "Connections currently open (idle or in use)",
+        )
+        .unwrap();
+        let pool_idle =
+            IntGauge::new("db_pool_idle_connections", "Open connections not in use").unwrap();
+        let pool_max = IntGauge::new(
+            "db_pool_max_connections",
+            "The most connections the pool will open",
+        )
+        .unwrap();
+
+        let metrics = Self {
+            registry: Registry::new(),
+            requests,
+            latency,
+            pool_connections,
+            pool_idle,
+            pool_max,
+        };
+        metrics.register(metrics.requests.clone());
+        metrics.register(metrics.latency.clone());
+        metrics.register(metrics.pool_connections.clone());
+        metrics.register(metrics.pool_idle.clone());
+        metrics.register(metrics.pool_max.clone());
+        metrics
+    }
+
+    /// Adds another collector (a counter, gauge...) to be scraped.
+    pub fn register(&self, collector: impl prometheus::core::Collector + 'static) {
+        self.registry
+            .register(Box::new(collector))
+            .expect("each metric is only registered once");
+    }
+
+    /// Everything, in Prometheus' text exposition format.
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    // The pool doesn't tell us when it changes, so we sample it whenever
+    // we're scraped.
+    fn observe_pool(&self, pool: &SqlitePool) {
+        self.pool_connections.set(pool.size() as i64);
+        self.pool_idle.set(pool.num_idle() as i64);
+        self.pool_max
+            .set(pool.options().get_max_connections() as i64);
+    }
+}
+
+/// Middleware that counts and times every request.
+///
+/// We label by the route *template* (`/person/:id`), not the actual
+/// path. Otherwise every id would be its own time series.
+pub async fn track(State(metrics): State<Metrics>, request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    metrics.requests.with_label_values(&labels).inc();
+    metrics.latency.with_label_values(&labels).observe(elapsed);
+    response
+}
+
+/// `GET /metrics`, for Prometheus to scrape.
+pub async fn scrape(
+    State(metrics): State<Metrics>,
+    State(pool): State<SqlitePool>,
+) -> impl IntoResponse {
+    metrics.observe_pool(&pool);
+    (
+        [(
+            header::CONTENT_TYPE,
+            TextEncoder::new().format_type().to_string(),
+        )],
+        metrics.encode(),
+    )
+}
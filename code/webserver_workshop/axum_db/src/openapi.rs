@@ -0,0 +1,211 @@
+use crate::error::{AppError, FieldError, Json, Problem};
+use crate::events::{self, PersonEvent};
+use crate::pages::render;
+use crate::person::{self, NewPerson, Person, PersonList, PersonPatch};
+use crate::state::AppState;
+use askama::Template;
+use axum::response::Html;
+use axum::routing::get;
+use axum::Router;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::Ref;
+use utoipa::{Modify, OpenApi, ToResponse};
+
+// The OpenAPI document is generated from the code: `#[utoipa::path]` on
+// each handler, and `ToSchema` on the types they send and receive. When
+// you add a route, list its handler in `paths` below - and run the tests
+// with `UPDATE_SNAPSHOTS=1` to see how the document changed.
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "axum_db", description = "The webserver workshop's person API."),
+    paths(
+        crate::json_path,
+        person::list_people,
+        person::get_person,
+        person::create_person,
+        person::replace_person,
+        person::update_person,
+        person::delete_person,
+        events::server_sent_events,
+        events::websocket,
+    ),
+    components(
+        schemas(crate::MyData, Person, NewPerson, PersonPatch, PersonList, PersonEvent, Problem, FieldError),
+        responses(BadRequest, Unauthorized, Forbidden, NotFound, Invalid, RateLimited, Overloaded, TimedOut),
+    ),
+    modifiers(&Tweaks),
+)]
+pub struct ApiDoc;
+
+// What the derive can't say for us:
+//
+// * How our bearer API keys work - so the routes can say which scope they
+//   need with `security(("api_key" = ["read"]))`.
+// * That there's no license. utoipa copies it from `Cargo.toml`, and we
+//   don't have one there.
+// * The limits (see `limits.rs`). They wrap every route, so every route
+//   can answer 429, 503 or 504 - rather than say so in each
+//   `#[utoipa::path]`, we add them here.
+struct Tweaks;
+
+impl Modify for Tweaks {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.license = None;
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        for path in openapi.paths.paths.values_mut() {
+            for operation in path.operations.values_mut() {
+                for (status, name) in [
+                    ("429", "RateLimited"),
+                    ("503", "Overloaded"),
+                    ("504", "TimedOut"),
+                ] {
+                    operation
+                        .responses
+                        .responses
+                        .insert(status.to_string(), Ref::from_response_name(name).into());
+                }
+            }
+        }
+    }
+}
+
+// The error responses every route shares. They're all `Problem`s. We
+// never build one - they only exist to be described - hence the
+// `allow(dead_code)`s.
+
+/// The path, query string or body couldn't be parsed at all.
+#[derive(ToResponse)]
+#[response(content_type = "application/problem+json")]
+#[allow(dead_code)]
+pub(crate) struct BadRequest(Problem);
+
+/// No API key, or one we don't know.
+#[derive(ToResponse)]
+#[response(content_type = "application/problem+json")]
+#[allow(dead_code)]
+pub(crate) struct Unauthorized(Problem);
+
+/// The API key doesn't have the scope this route needs.
+#[derive(ToResponse)]
+#[response(content_type = "application/problem+json")]
+#[allow(dead_code)]
+pub(crate) struct Forbidden(Problem);
+
+/// There's nobody with that id.
+#[derive(ToResponse)]
+#[response(content_type = "application/problem+json")]
+#[allow(dead_code)]
+pub(crate) struct NotFound(Problem);
+
+/// Some fields were invalid - `errors` says which.
+#[derive(ToResponse)]
+#[response(content_type = "application/problem+json")]
+#[allow(dead_code)]
+pub(crate) struct Invalid(Problem);
+
+/// This client is sending too many requests.
+#[derive(ToResponse)]
+#[response(
+    content_type = "application/problem+json",
+    headers(("retry-after" = u64, description = "Seconds until the next request will be allowed"))
+)]
+#[allow(dead_code)]
+pub(crate) struct RateLimited(Problem);
+
+/// The server is already handling as many requests as it will.
+#[derive(ToResponse)]
+#[response(
+    content_type = "application/problem+json",
+    headers(("retry-after" = u64, description = "Seconds to wait before trying again"))
+)]
+#[allow(dead_code)]
+pub(crate) struct Overloaded(Problem);
+
+/// The request took longer than the server allows.
+#[derive(ToResponse)]
+#[response(
+    content_type = "application/problem+json",
+    headers(("retry-after" = u64, description = "Seconds to wait before trying again"))
+)]
+#[allow(dead_code)]
+pub(crate) struct TimedOut(Problem);
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(openapi_json))
+        .route("/docs", get(docs_page))
+}
+
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+// The docs page is one of our own templates, built from the same
+// document - so it works offline, with no JavaScript.
+
+#[derive(Template)]
+#[template(path = "docs.html")]
+struct DocsTemplate {
+    title: String,
+    description: String,
+    operations: Vec<Operation>,
+    schemas: Vec<(String, String)>,
+}
+
+struct Operation {
+    method: String,
+    path: String,
+    summary: String,
+    /// The API key scope needed, if any.
+    scope: Option<String>,
+    statuses: String,
+}
+
+const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+async fn docs_page() -> Result<Html<String>, AppError> {
+    // It's easier to walk the document as JSON than through utoipa's
+    // builder types.
+    let doc = serde_json::to_value(ApiDoc::openapi()).expect("the document is valid JSON");
+    let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+
+    let mut operations = Vec::new();
+    for (path, item) in doc["paths"].as_object().into_iter().flatten() {
+        for method in METHODS {
+            let operation = &item[method];
+            if operation.is_null() {
+                continue;
+            }
+            let statuses: Vec<&str> = operation["responses"]
+                .as_object()
+                .map(|responses| responses.keys().map(String::as_str).collect())
+                .unwrap_or_default();
+            operations.push(Operation {
+                method: method.to_uppercase(),
+                path: path.clone(),
+                summary: text(&operation["summary"]),
+                scope: operation["security"][0]["api_key"].get(0).map(text),
+                statuses: statuses.join(", "),
+            });
+        }
+    }
+
+    let schemas = doc["components"]["schemas"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, schema)| (name.clone(), serde_json::to_string_pretty(schema).unwrap()))
+        .collect();
+
+    render(&DocsTemplate {
+        title: text(&doc["info"]["title"]),
+        description: text(&doc["info"]["description"]),
+        operations,
+        schemas,
+    })
+}
@@ -0,0 +1,78 @@
+use crate::auth::Reader;
+use crate::config::Config;
+use crate::error::{AppError, Path, Query};
+use crate::person::Person;
+use crate::repository::{PersonFilter, PersonRepository};
+use crate::state::AppState;
+use askama::Template;
+use axum::extract::State;
+use axum::response::Html;
+use axum::routing::get;
+use axum::Router;
+use std::sync::Arc;
+
+// The templates live in `templates/`, and are checked (and compiled into
+// the binary) at build time. Anything we print is HTML-escaped.
+//
+// `/people` shows the same rows as `GET /person`, so it needs the same
+// `read` key.
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/hello/:n", get(html_path))
+        .route("/people", get(people_page))
+}
+
+/// Renders a template, turning a failure into a `500` instead of a panic.
+pub fn render(template: &impl Template) -> Result<Html<String>, AppError> {
+    Ok(Html(template.render()?))
+}
+
+#[derive(Template)]
+#[template(path = "hello.html")]
+struct HelloTemplate {
+    n: u32,
+}
+
+async fn html_path(Path(n): Path<u32>) -> Result<Html<String>, AppError> {
+    render(&HelloTemplate { n })
+}
+
+#[derive(Template)]
+#[template(path = "people.html")]
+struct PeopleTemplate {
+    people: Vec<Person>,
+    page: u32,
+    previous: Option<u32>,
+    next: Option<u32>,
+}
+
+#[derive(serde::Deserialize)]
+struct PageParams {
+    page: Option<u32>,
+}
+
+async fn people_page(
+    _: Reader,
+    Query(params): Query<PageParams>,
+    State(repository): State<Arc<dyn PersonRepository>>,
+    State(config): State<Arc<Config>>,
+) -> Result<Html<String>, AppError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = config.default_per_page;
+
+    // Ask for one extra row, so we know whether there's a next page.
+    let offset = (page as u64 - 1) * per_page as u64;
+    let mut people = repository
+        .list(&PersonFilter::default(), per_page + 1, offset)
+        .await?;
+    let has_next = people.len() > per_page as usize;
+    people.truncate(per_page as usize);
+
+    render(&PeopleTemplate {
+        people,
+        page,
+        previous: (page > 1).then(|| page - 1),
+        next: has_next.then(|| page + 1),
+    })
+}
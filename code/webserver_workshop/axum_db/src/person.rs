@@ -0,0 +1,339 @@
+use crate::auth::{Reader, Writer};
+use crate::config::Config;
+use crate::error::{AppError, FieldError, Json, Path, Query};
+use crate::events::{Events, PersonEvent};
+use crate::openapi::{BadRequest, Forbidden, Invalid, NotFound, Unauthorized};
+use crate::repository::{PersonFilter, PersonRepository};
+use crate::state::AppState;
+use axum::extract::{FromRef, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use std::sync::Arc;
+
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_AGE: i32 = 150;
+
+// Reading needs an API key with the `read` scope, changing anything needs
+// `write` (see `auth.rs`). The key comes first in each handler, so a
+// request without one gets a 401 before we look at its body.
+//
+// Each successful change is also published as a `PersonEvent`, for the
+// `/events` and `/ws` streams (see `events.rs`).
+//
+// The handlers work with any `PersonRepository` (see `repository.rs`).
+// `app` picks `dyn PersonRepository`, so the backend can be chosen at
+// startup.
+pub fn routes<R>() -> Router<AppState>
+where
+    R: PersonRepository + ?Sized + 'static,
+    Arc<R>: FromRef<AppState>,
+{
+    Router::new()
+        .route("/person", get(list_people::<R>).post(create_person::<R>))
+        .route(
+            "/person/:id",
+            get(get_person::<R>)
+                .put(replace_person::<R>)
+                .patch(update_person::<R>)
+                .delete(delete_person::<R>),
+        )
+}
+
+#[derive(serde::Serialize, sqlx::FromRow, utoipa::ToSchema, Clone, Debug, PartialEq)]
+pub struct Person {
+    pub id: i32,
+    pub name: String,
+    pub age: i32,
+}
+
+/// The body of a `POST` or `PUT`: every field is required.
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct NewPerson {
+    pub name: String,
+    pub age: i32,
+}
+
+/// The body of a `PATCH`: only the fields you want to change.
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct PersonPatch {
+    pub name: Option<String>,
+    pub age: Option<i32>,
+}
+
+fn check_name(name: &str, errors: &mut Vec<FieldError>) {
+    if name.trim().is_empty() {
+        errors.push(FieldError::new("name", "must not be empty"));
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        errors.push(FieldError::new(
+            "name",
+            format!("must be at most {MAX_NAME_LENGTH} characters"),
+        ));
+    }
+}
+
+fn check_age(age: i32, errors: &mut Vec<FieldError>) {
+    if !(0..=MAX_AGE).contains(&age) {
+        errors.push(FieldError::new(
+            "age",
+            format!("must be between 0 and {MAX_AGE}"),
+        ));
+    }
+}
+
+impl NewPerson {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let mut errors = Vec::new();
+        check_name(&self.name, &mut errors);
+        check_age(self.age, &mut errors);
+        AppError::validation(errors)
+    }
+}
+
+impl PersonPatch {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let mut errors = Vec::new();
+        if self.name.is_none() && self.age.is_none() {
+            errors.push(FieldError::new(
+                "body",
+                "must change at least one of `name` or `age`",
+            ));
+        }
+        if let Some(name) = &self.name {
+            check_name(name, &mut errors);
+        }
+        if let Some(age) = self.age {
+            check_age(age, &mut errors);
+        }
+        AppError::validation(errors)
+    }
+}
+
+/// Query parameters for `GET /person`.
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Which page, starting from 1.
+    page: Option<u32>,
+    /// How many people per page.
+    per_page: Option<u32>,
+    /// Only people whose name contains this text.
+    name: Option<String>,
+    /// Only people of exactly this age.
+    age: Option<i32>,
+}
+
+/// One page of people.
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct PersonList {
+    items: Vec<Person>,
+    page: u32,
+    per_page: u32,
+    total: i64,
+}
+
+/// List people, a page at a time.
+#[utoipa::path(
+    get,
+    path = "/person",
+    params(ListParams),
+    responses(
+        (status = 200, body = PersonList),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 422, response = Invalid),
+    ),
+    security(("api_key" = ["read"])),
+)]
+async fn list_people<R: PersonRepository + ?Sized>(
+    _: Reader,
+    Query(params): Query<ListParams>,
+    State(people): State<Arc<R>>,
+    State(config): State<Arc<Config>>,
+) -> Result<Json<PersonList>, AppError> {
+    let page = params.page.unwrap_or(1);
+    let per_page = params.per_page.unwrap_or(config.default_per_page);
+    let mut errors = Vec::new();
+    if page == 0 {
+        errors.push(FieldError::new("page", "must be at least 1"));
+    }
+    if !(1..=config.max_per_page).contains(&per_page) {
+        errors.push(FieldError::new(
+            "per_page",
+            format!("must be between 1 and {}", config.max_per_page),
+        ));
+    }
+    AppError::validation(errors)?;
+
+    let filter = PersonFilter {
+        name: params.name,
+        age: params.age,
+    };
+    let total = people.count(&filter).await?;
+    let offset = (page as u64 - 1) * per_page as u64;
+    let items = people.list(&filter, per_page, offset).await?;
+
+    Ok(Json(PersonList {
+        items,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+/// Look up one person.
+#[utoipa::path(
+    get,
+    path = "/person/{id}",
+    params(("id" = i32, Path, description = "The person's id")),
+    responses(
+        (status = 200, body = Person),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, response = NotFound),
+    ),
+    security(("api_key" = ["read"])),
+)]
+async fn get_person<R: PersonRepository + ?Sized>(
+    _: Reader,
+    Path(id): Path<i32>,
+    State(people): State<Arc<R>>,
+) -> Result<Json<Person>, AppError> {
+    let person = people.get(id).await?.ok_or(AppError::NotFound)?;
+
+    Ok(Json(person))
+}
+
+/// Add a person.
+#[utoipa::path(
+    post,
+    path = "/person",
+    request_body = NewPerson,
+    responses(
+        (status = 201, body = Person, headers(("location" = String, description = "The new person's URL"))),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 422, response = Invalid),
+    ),
+    security(("api_key" = ["write"])),
+)]
+async fn create_person<R: PersonRepository + ?Sized>(
+    _: Writer,
+    State(people): State<Arc<R>>,
+    State(events): State<Events>,
+    Json(new_person): Json<NewPerson>,
+) -> Result<impl IntoResponse, AppError> {
+    new_person.validate()?;
+
+    let person = people.create(&new_person).await?;
+    events.publish(PersonEvent::Created {
+        person: person.clone(),
+    });
+
+    let location = format!("/person/{}", person.id);
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(person),
+    ))
+}
+
+/// Replace everything about a person.
+#[utoipa::path(
+    put,
+    path = "/person/{id}",
+    params(("id" = i32, Path, description = "The person's id")),
+    request_body = NewPerson,
+    responses(
+        (status = 200, body = Person),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, response = NotFound),
+        (status = 422, response = Invalid),
+    ),
+    security(("api_key" = ["write"])),
+)]
+async fn replace_person<R: PersonRepository + ?Sized>(
+    _: Writer,
+    Path(id): Path<i32>,
+    State(people): State<Arc<R>>,
+    State(events): State<Events>,
+    Json(new_person): Json<NewPerson>,
+) -> Result<Json<Person>, AppError> {
+    new_person.validate()?;
+
+    let person = people
+        .replace(id, &new_person)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    events.publish(PersonEvent::Updated {
+        person: person.clone(),
+    });
+
+    Ok(Json(person))
+}
+
+/// Change some of a person's fields.
+#[utoipa::path(
+    patch,
+    path = "/person/{id}",
+    params(("id" = i32, Path, description = "The person's id")),
+    request_body = PersonPatch,
+    responses(
+        (status = 200, body = Person),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, response = NotFound),
+        (status = 422, response = Invalid),
+    ),
+    security(("api_key" = ["write"])),
+)]
+async fn update_person<R: PersonRepository + ?Sized>(
+    _: Writer,
+    Path(id): Path<i32>,
+    State(people): State<Arc<R>>,
+    State(events): State<Events>,
+    Json(patch): Json<PersonPatch>,
+) -> Result<Json<Person>, AppError> {
+    patch.validate()?;
+
+    let person = people.update(id, &patch).await?.ok_or(AppError::NotFound)?;
+    events.publish(PersonEvent::Updated {
+        person: person.clone(),
+    });
+
+    Ok(Json(person))
+}
+
+/// Delete a person.
+#[utoipa::path(
+    delete,
+    path = "/person/{id}",
+    params(("id" = i32, Path, description = "The person's id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 400, response = BadRequest),
+        (status = 401, response = Unauthorized),
+        (status = 403, response = Forbidden),
+        (status = 404, response = NotFound),
+    ),
+    security(("api_key" = ["write"])),
+)]
+async fn delete_person<R: PersonRepository + ?Sized>(
+    _: Writer,
+    Path(id): Path<i32>,
+    State(people): State<Arc<R>>,
+    State(events): State<Events>,
+) -> Result<StatusCode, AppError> {
+    if !people.delete(id).await? {
+        return Err(AppError::NotFound);
+    }
+    events.publish(PersonEvent::Deleted { id });
+    Ok(StatusCode::NO_CONTENT)
+}
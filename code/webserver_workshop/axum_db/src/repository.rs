@@ -0,0 +1,143 @@
+use crate::config::{hide_password, DatabaseConfig};
+use crate::person::{NewPerson, Person, PersonPatch};
+use crate::shutdown::Drain;
+use axum::async_trait;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
+mod sqlite;
+
+pub use memory::MemoryRepository;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresRepository;
+pub use sqlite::SqliteRepository;
+
+// The person handlers don't know where people are kept: they talk to a
+// `PersonRepository`. There are three:
+//
+// * `SqliteRepository` - the `my_data` table, in the same database as
+//   everything else. The default.
+// * `PostgresRepository` - a `my_data` table in Postgres. Only built with
+//   `--features postgres`.
+// * `MemoryRepository` - a `HashMap`. Gone when the server stops.
+//
+// Whichever one holds the people, API keys stay in the SQLite database.
+//
+// They all have to behave the same way - `tests/repository.rs` runs one
+// set of tests against each.
+
+/// Which people `list` and `count` include. `None` means "anyone".
+#[derive(Debug, Clone, Default)]
+pub struct PersonFilter {
+    /// Their name contains this, ignoring (ASCII) case. `%` and `_`
+    /// match themselves, not "anything".
+    pub name: Option<String>,
+    /// Exactly this age.
+    pub age: Option<i32>,
+}
+
+/// Where people are kept. Both database backends use sqlx, so their
+/// errors are `sqlx::Error`s; someone who isn't there is `None` (or
+/// `false`), not an error.
+#[async_trait]
+pub trait PersonRepository: Send + Sync {
+    /// People matching `filter`, in `id` order, skipping the first
+    /// `offset`, and at most `limit` of them.
+    async fn list(
+        &self,
+        filter: &PersonFilter,
+        limit: u32,
+        offset: u64,
+    ) -> Result<Vec<Person>, sqlx::Error>;
+
+    /// How many people match `filter`.
+    async fn count(&self, filter: &PersonFilter) -> Result<i64, sqlx::Error>;
+
+    async fn get(&self, id: i32) -> Result<Option<Person>, sqlx::Error>;
+
+    /// Adds a person, with a new id - one that's never been used before.
+    async fn create(&self, person: &NewPerson) -> Result<Person, sqlx::Error>;
+
+    async fn replace(&self, id: i32, person: &NewPerson) -> Result<Option<Person>, sqlx::Error>;
+
+    /// Changes just the fields that are `Some`.
+    async fn update(&self, id: i32, patch: &PersonPatch) -> Result<Option<Person>, sqlx::Error>;
+
+    /// `true` if there was someone to delete.
+    async fn delete(&self, id: i32) -> Result<bool, sqlx::Error>;
+
+    /// Called once the server has stopped. A backend with connections of
+    /// its own closes them here.
+    async fn close(&self, _drain: Drain) {}
+}
+
+/// Where `--people-url` says to keep people.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+    Memory,
+}
+
+impl Backend {
+    /// `None` means the SQLite database; otherwise a `postgres://` URL,
+    /// or `memory:`.
+    pub fn for_url(url: Option<&str>) -> Result<Self, String> {
+        match url {
+            None => Ok(Backend::Sqlite),
+            Some("memory:") => Ok(Backend::Memory),
+            Some(url) if url.starts_with("postgres:") || url.starts_with("postgresql:") => {
+                if cfg!(feature = "postgres") {
+                    Ok(Backend::Postgres)
+                } else {
+                    Err(format!(
+                        "people_url `{}` needs axum_db built with `--features postgres`",
+                        hide_password(url)
+                    ))
+                }
+            }
+            Some(url) => Err(format!(
+                "people_url `{}` must be `memory:` or start with `postgres:`",
+                hide_password(url)
+            )),
+        }
+    }
+}
+
+/// Opens the repository the config asks for. `pool` is the SQLite
+/// database, which the default backend shares.
+pub async fn connect(
+    config: &DatabaseConfig,
+    pool: &SqlitePool,
+) -> Result<Arc<dyn PersonRepository>, sqlx::Error> {
+    // `Config::validate` has already checked the URL.
+    let backend = Backend::for_url(config.people_url.as_deref())
+        .map_err(|problem| sqlx::Error::Configuration(problem.into()))?;
+    Ok(match backend {
+        Backend::Sqlite => Arc::new(SqliteRepository::new(pool.clone())),
+        Backend::Memory => Arc::new(MemoryRepository::seeded()),
+        #[cfg(feature = "postgres")]
+        Backend::Postgres => Arc::new(PostgresRepository::connect(config).await?),
+        #[cfg(not(feature = "postgres"))]
+        Backend::Postgres => {
+            unreachable!("`Backend::for_url` rejects Postgres URLs without the feature")
+        }
+    })
+}
+
+// `LIKE` treats `%` and `_` as wildcards. We escape them (and the escape
+// character) so a name filter only ever means "contains".
+fn like_pattern(text: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
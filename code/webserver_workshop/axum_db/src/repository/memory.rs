@@ -0,0 +1,127 @@
+use super::{PersonFilter, PersonRepository};
+use crate::person::{NewPerson, Person, PersonPatch};
+use axum::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// People in a `HashMap` - no database at all. Handy for tests and demos,
+/// but everything is lost when the server stops.
+#[derive(Default)]
+pub struct MemoryRepository {
+    // A plain `Mutex` is fine: we never hold it across an `.await`.
+    people: Mutex<People>,
+}
+
+#[derive(Default)]
+struct People {
+    by_id: HashMap<i32, Person>,
+    // Like `AUTOINCREMENT`: ids keep going up, even after a delete.
+    last_id: i32,
+}
+
+impl MemoryRepository {
+    /// No people at all.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Alice and Bob, like a freshly migrated database.
+    pub fn seeded() -> Self {
+        let mut people = People::default();
+        people.insert("Alice".to_string(), 42);
+        people.insert("Bob".to_string(), 69);
+        Self {
+            people: Mutex::new(people),
+        }
+    }
+}
+
+impl People {
+    fn insert(&mut self, name: String, age: i32) -> Person {
+        self.last_id += 1;
+        let person = Person {
+            id: self.last_id,
+            name,
+            age,
+        };
+        self.by_id.insert(person.id, person.clone());
+        person
+    }
+
+    // Matching people, in `id` order - the map has no order of its own.
+    fn matching(&self, filter: &PersonFilter) -> Vec<&Person> {
+        let name = filter.name.as_ref().map(|name| name.to_ascii_lowercase());
+        let mut people: Vec<&Person> = self
+            .by_id
+            .values()
+            .filter(|person| match &name {
+                Some(name) => person.name.to_ascii_lowercase().contains(name.as_str()),
+                None => true,
+            })
+            .filter(|person| filter.age.is_none_or(|age| person.age == age))
+            .collect();
+        people.sort_by_key(|person| person.id);
+        people
+    }
+}
+
+#[async_trait]
+impl PersonRepository for MemoryRepository {
+    async fn list(
+        &self,
+        filter: &PersonFilter,
+        limit: u32,
+        offset: u64,
+    ) -> Result<Vec<Person>, sqlx::Error> {
+        let people = self.people.lock().unwrap();
+        Ok(people
+            .matching(filter)
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn count(&self, filter: &PersonFilter) -> Result<i64, sqlx::Error> {
+        Ok(self.people.lock().unwrap().matching(filter).len() as i64)
+    }
+
+    async fn get(&self, id: i32) -> Result<Option<Person>, sqlx::Error> {
+        Ok(self.people.lock().unwrap().by_id.get(&id).cloned())
+    }
+
+    async fn create(&self, person: &NewPerson) -> Result<Person, sqlx::Error> {
+        Ok(self
+            .people
+            .lock()
+            .unwrap()
+            .insert(person.name.clone(), person.age))
+    }
+
+    async fn replace(&self, id: i32, person: &NewPerson) -> Result<Option<Person>, sqlx::Error> {
+        let mut people = self.people.lock().unwrap();
+        Ok(people.by_id.get_mut(&id).map(|existing| {
+            existing.name = person.name.clone();
+            existing.age = person.age;
+            existing.clone()
+        }))
+    }
+
+    async fn update(&self, id: i32, patch: &PersonPatch) -> Result<Option<Person>, sqlx::Error> {
+        let mut people = self.people.lock().unwrap();
+        Ok(people.by_id.get_mut(&id).map(|existing| {
+            if let Some(name) = &patch.name {
+                existing.name = name.clone();
+            }
+            if let Some(age) = patch.age {
+                existing.age = age;
+            }
+            existing.clone()
+        }))
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, sqlx::Error> {
+        Ok(self.people.lock().unwrap().by_id.remove(&id).is_some())
+    }
+}
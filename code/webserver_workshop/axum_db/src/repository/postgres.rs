@@ -0,0 +1,125 @@
+use super::{like_pattern, PersonFilter, PersonRepository};
+use crate::config::DatabaseConfig;
+use crate::db;
+use crate::person::{NewPerson, Person, PersonPatch};
+use crate::shutdown::{self, Drain};
+use axum::async_trait;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+
+/// Postgres has its own migrations - the SQL isn't quite the same.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./postgres_migrations");
+
+/// People in a `my_data` table in Postgres.
+#[derive(Clone)]
+pub struct PostgresRepository {
+    pool: PgPool,
+}
+
+impl PostgresRepository {
+    /// Connects to `config.people_url`, with the same pool settings as
+    /// the SQLite database, and brings the schema up to date.
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let url = config.people_url.as_deref().unwrap_or_default();
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.acquire_timeout)
+            .connect(url)
+            .await?;
+        Self::new(pool).await
+    }
+
+    /// Migrates `pool`, and keeps people there.
+    pub async fn new(pool: PgPool) -> Result<Self, sqlx::Error> {
+        MIGRATOR.run(&pool).await?;
+        Ok(Self { pool })
+    }
+}
+
+// The same filters as the SQLite version. Postgres numbers its
+// placeholders (`$1`), but `push_bind` takes care of that.
+fn push_filters<'a>(query: &mut QueryBuilder<'a, Postgres>, filter: &'a PersonFilter) {
+    query.push(" WHERE 1 = 1");
+    if let Some(name) = &filter.name {
+        // Postgres' `LIKE` is case-sensitive; SQLite's isn't.
+        query
+            .push(" AND name ILIKE ")
+            .push_bind(like_pattern(name))
+            .push(r" ESCAPE '\'");
+    }
+    if let Some(age) = filter.age {
+        query.push(" AND age = ").push_bind(age);
+    }
+}
+
+#[async_trait]
+impl PersonRepository for PostgresRepository {
+    async fn list(
+        &self,
+        filter: &PersonFilter,
+        limit: u32,
+        offset: u64,
+    ) -> Result<Vec<Person>, sqlx::Error> {
+        let mut select = QueryBuilder::new("SELECT * FROM my_data");
+        push_filters(&mut select, filter);
+        select
+            .push(" ORDER BY id LIMIT ")
+            .push_bind(limit as i64)
+            .push(" OFFSET ")
+            .push_bind(offset as i64);
+        db::timed("list people", select.build_query_as().fetch_all(&self.pool)).await
+    }
+
+    async fn count(&self, filter: &PersonFilter) -> Result<i64, sqlx::Error> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM my_data");
+        push_filters(&mut count, filter);
+        db::timed(
+            "count people",
+            count.build_query_scalar().fetch_one(&self.pool),
+        )
+        .await
+    }
+
+    async fn get(&self, id: i32) -> Result<Option<Person>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM my_data WHERE id = $1").bind(id);
+        db::timed("select person", query.fetch_optional(&self.pool)).await
+    }
+
+    async fn create(&self, person: &NewPerson) -> Result<Person, sqlx::Error> {
+        // A `SERIAL`'s sequence never hands out the same id twice.
+        let query = sqlx::query_as("INSERT INTO my_data (name, age) VALUES ($1, $2) RETURNING *")
+            .bind(&person.name)
+            .bind(person.age);
+        db::timed("insert person", query.fetch_one(&self.pool)).await
+    }
+
+    async fn replace(&self, id: i32, person: &NewPerson) -> Result<Option<Person>, sqlx::Error> {
+        let query =
+            sqlx::query_as("UPDATE my_data SET name = $1, age = $2 WHERE id = $3 RETURNING *")
+                .bind(&person.name)
+                .bind(person.age)
+                .bind(id);
+        db::timed("replace person", query.fetch_optional(&self.pool)).await
+    }
+
+    async fn update(&self, id: i32, patch: &PersonPatch) -> Result<Option<Person>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "UPDATE my_data SET name = COALESCE($1, name), age = COALESCE($2, age) WHERE id = $3 RETURNING *",
+        )
+        .bind(&patch.name)
+        .bind(patch.age)
+        .bind(id);
+        db::timed("update person", query.fetch_optional(&self.pool)).await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, sqlx::Error> {
+        let query = sqlx::query("DELETE FROM my_data WHERE id = $1").bind(id);
+        let result = db::timed("delete person", query.execute(&self.pool)).await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn close(&self, drain: Drain) {
+        shutdown::close_pool(&self.pool, drain).await;
+    }
+}
@@ -0,0 +1,100 @@
+use super::{like_pattern, PersonFilter, PersonRepository};
+use crate::db;
+use crate::person::{NewPerson, Person, PersonPatch};
+use axum::async_trait;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+/// People in the `my_data` table of the SQLite database.
+#[derive(Clone)]
+pub struct SqliteRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRepository {
+    /// `pool` should already be migrated.
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+// Adds the `WHERE` clause for the list filters. It's shared by the
+// count query and the page query, so they always agree.
+fn push_filters<'a>(query: &mut QueryBuilder<'a, Sqlite>, filter: &'a PersonFilter) {
+    query.push(" WHERE 1 = 1");
+    if let Some(name) = &filter.name {
+        query
+            .push(" AND name LIKE ")
+            .push_bind(like_pattern(name))
+            .push(r" ESCAPE '\'");
+    }
+    if let Some(age) = filter.age {
+        query.push(" AND age = ").push_bind(age);
+    }
+}
+
+#[async_trait]
+impl PersonRepository for SqliteRepository {
+    async fn list(
+        &self,
+        filter: &PersonFilter,
+        limit: u32,
+        offset: u64,
+    ) -> Result<Vec<Person>, sqlx::Error> {
+        let mut select = QueryBuilder::new("SELECT * FROM my_data");
+        push_filters(&mut select, filter);
+        select
+            .push(" ORDER BY id LIMIT ")
+            .push_bind(limit as i64)
+            .push(" OFFSET ")
+            .push_bind(offset as i64);
+        db::timed("list people", select.build_query_as().fetch_all(&self.pool)).await
+    }
+
+    async fn count(&self, filter: &PersonFilter) -> Result<i64, sqlx::Error> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM my_data");
+        push_filters(&mut count, filter);
+        db::timed(
+            "count people",
+            count.build_query_scalar().fetch_one(&self.pool),
+        )
+        .await
+    }
+
+    async fn get(&self, id: i32) -> Result<Option<Person>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM my_data WHERE id = ?").bind(id);
+        db::timed("select person", query.fetch_optional(&self.pool)).await
+    }
+
+    async fn create(&self, person: &NewPerson) -> Result<Person, sqlx::Error> {
+        // `AUTOINCREMENT` means ids aren't reused, even after a delete.
+        let query = sqlx::query_as("INSERT INTO my_data (name, age) VALUES (?, ?) RETURNING *")
+            .bind(&person.name)
+            .bind(person.age);
+        db::timed("insert person", query.fetch_one(&self.pool)).await
+    }
+
+    async fn replace(&self, id: i32, person: &NewPerson) -> Result<Option<Person>, sqlx::Error> {
+        let query = sqlx::query_as("UPDATE my_data SET name = ?, age = ? WHERE id = ? RETURNING *")
+            .bind(&person.name)
+            .bind(person.age)
+            .bind(id);
+        db::timed("replace person", query.fetch_optional(&self.pool)).await
+    }
+
+    async fn update(&self, id: i32, patch: &PersonPatch) -> Result<Option<Person>, sqlx::Error> {
+        // `COALESCE` keeps the existing value when we bind a NULL.
+        let query = sqlx::query_as(
+            "UPDATE my_data SET name = COALESCE(?, name), age = COALESCE(?, age) WHERE id = ? RETURNING *",
+        )
+        .bind(&patch.name)
+        .bind(patch.age)
+        .bind(id);
+        db::timed("update person", query.fetch_optional(&self.pool)).await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, sqlx::Error> {
+        let query = sqlx::query("DELETE FROM my_data WHERE id = ?").bind(id);
+        let result = db::timed("delete person", query.execute(&self.pool)).await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,80 @@
+use axum::Router;
+use std::future::{Future, IntoFuture};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+
+/// How `serve` finished.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Drain {
+    /// Every request in flight finished.
+    Complete,
+    /// The drain timeout ran out first; whatever was still running was
+    /// abandoned.
+    TimedOut,
+}
+
+// `signal` is shared with the hello servers. See `workshop_util::shutdown`
+// for why we shut down gracefully at all; what this module adds is a limit
+// on how long that takes, and closing the database cleanly afterwards.
+pub use workshop_util::shutdown::signal;
+
+/// Serves `app` until `shutdown` completes. Then it stops accepting
+/// connections, and gives the requests already running up to
+/// `drain_timeout` to finish.
+///
+/// `main` passes `signal()` as `shutdown`; the tests pass something they
+/// control.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+    drain_timeout: Duration,
+) -> std::io::Result<Drain> {
+    // `with_graceful_shutdown` waits for in-flight requests for as long
+    // as they take. To put a limit on that, we need to know when the
+    // draining started.
+    let draining = Arc::new(Notify::new());
+    let signal = {
+        let draining = draining.clone();
+        async move {
+            shutdown.await;
+            tracing::info!("shutting down: finishing the requests in flight");
+            // `notify_one` keeps a permit if nobody is waiting yet.
+            draining.notify_one();
+        }
+    };
+    // The rate limiter needs to know who's asking.
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(signal)
+        .into_future();
+
+    tokio::select! {
+        result = server => result.map(|_| Drain::Complete),
+        _ = async {
+            draining.notified().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => {
+            tracing::warn!(?drain_timeout, "shutting down: gave up waiting for requests in flight");
+            Ok(Drain::TimedOut)
+        }
+    }
+}
+
+/// Closes the pool once the server has stopped, so SQLite can finish its
+/// writes (and checkpoint the write-ahead log) before we exit. Any sqlx
+/// pool will do - the Postgres backend closes its own with this too.
+pub async fn close_pool<DB: sqlx::Database>(pool: &sqlx::Pool<DB>, drain: Drain) {
+    match drain {
+        Drain::Complete => pool.close().await,
+        // `close` waits for every connection to come back, and abandoned
+        // requests may never return theirs. Close the idle ones, and go.
+        Drain::TimedOut => {
+            let _ = tokio::time::timeout(Duration::from_secs(1), pool.close()).await;
+        }
+    }
+    tracing::info!("database pool closed");
+}
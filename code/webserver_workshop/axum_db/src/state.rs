@@ -0,0 +1,41 @@
+use crate::config::Config;
+use crate::events::Events;
+use crate::metrics::Metrics;
+use crate::repository::{PersonRepository, SqliteRepository};
+use axum::extract::FromRef;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+/// Everything the handlers share. Unlike an `Extension` layer, forgetting
+/// to provide it is a compile error rather than a 500 at runtime.
+///
+/// `FromRef` lets a handler extract just the part it needs - for example
+/// `State(pool): State<SqlitePool>`.
+///
+/// `pool` is always SQLite (API keys live there). The people are wherever
+/// `people` keeps them - by default, the same database.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub people: Arc<dyn PersonRepository>,
+    pub config: Arc<Config>,
+    pub metrics: Metrics,
+    pub events: Events,
+}
+
+impl AppState {
+    /// State with the default `Config`, keeping people in `pool`.
+    pub fn new(pool: SqlitePool) -> Self {
+        Self::with_config(pool, Config::default())
+    }
+
+    pub fn with_config(pool: SqlitePool, config: Config) -> Self {
+        Self {
+            people: Arc::new(SqliteRepository::new(pool.clone())),
+            pool,
+            config: Arc::new(config),
+            metrics: Metrics::new(),
+            events: Events::default(),
+        }
+    }
+}
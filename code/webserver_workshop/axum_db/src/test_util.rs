@@ -0,0 +1,88 @@
+// Helpers for integration tests - ours, and `axum_db_cache`'s. Only built
+// with the `test-util` feature, which both crates turn on for their tests.
+
+use crate::auth::{self, Scope};
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use tower::ServiceExt;
+
+/// The API key every test database starts with. It can read and write.
+pub const TEST_TOKEN: &str = "test-token-with-every-scope";
+
+/// A fresh, migrated, in-memory database, with `TEST_TOKEN` in it.
+pub async fn test_pool() -> SqlitePool {
+    // Each connection to `sqlite::memory:` gets its own, empty database -
+    // so the pool must only ever have one connection, and keep it open.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    crate::db::migrate(&pool).await.unwrap();
+    auth::add_api_key(&pool, "tests", TEST_TOKEN, &[Scope::Read, Scope::Write])
+        .await
+        .unwrap();
+    pool
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: serde_json::Value,
+}
+
+/// Sends one request to the router, with `TEST_TOKEN`, and parses the
+/// (JSON) response.
+pub async fn send(
+    app: Router,
+    method: Method,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> TestResponse {
+    send_as(app, Some(TEST_TOKEN), method, uri, body).await
+}
+
+/// Like `send`, but with a different API key - or none at all.
+pub async fn send_as(
+    app: Router,
+    token: Option<&str>,
+    method: Method,
+    uri: &str,
+    body: Option<serde_json::Value>,
+) -> TestResponse {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    let request = match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    };
+    let response = app.oneshot(request.unwrap()).await.unwrap();
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body = if bytes.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap()
+    };
+    TestResponse {
+        status,
+        headers,
+        body,
+    }
+}
+
+pub async fn get(app: Router, uri: &str) -> TestResponse {
+    send(app, Method::GET, uri, None).await
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use axum::Router;
+use axum_db::auth::{self, Scope};
+use axum_db::AppState;
+use common::{send_as, test_pool};
+use serde_json::json;
+
+// An app with one extra key, made the way `--create-api-key` makes them.
+async fn app_with_key(scopes: &[Scope]) -> (Router, String) {
+    let pool = test_pool().await;
+    let token = auth::create_api_key(&pool, "under test", scopes)
+        .await
+        .unwrap();
+    (axum_db::app(AppState::new(pool)), token)
+}
+
+#[tokio::test]
+async fn no_key_is_401() {
+    let (app, _) = app_with_key(&[Scope::Read]).await;
+    let response = send_as(app, None, Method::GET, "/person/1", None).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers[header::WWW_AUTHENTICATE], "Bearer");
+    assert_eq!(
+        response.headers[header::CONTENT_TYPE],
+        "application/problem+json"
+    );
+    assert_eq!(response.body["status"], 401);
+}
+
+#[tokio::test]
+async fn unknown_key_is_401() {
+    let (app, _) = app_with_key(&[Scope::Read]).await;
+    let response = send_as(
+        app,
+        Some(&auth::generate_token()),
+        Method::GET,
+        "/person/1",
+        None,
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(response.body["detail"], "unknown API key");
+}
+
+#[tokio::test]
+async fn other_schemes_are_401() {
+    let (app, token) = app_with_key(&[Scope::Read]).await;
+    let request = axum::http::Request::builder()
+        .uri("/person/1")
+        .header(header::AUTHORIZATION, format!("Basic {token}"))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = tower::ServiceExt::oneshot(app, request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn read_scope_can_read_but_not_write() {
+    let (app, token) = app_with_key(&[Scope::Read]).await;
+    let token = Some(token.as_str());
+
+    let response = send_as(app.clone(), token, Method::GET, "/person/1", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = send_as(app.clone(), token, Method::GET, "/person", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let body = json!({ "name": "Carol", "age": 30 });
+    let response = send_as(app.clone(), token, Method::POST, "/person", Some(body)).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.body["detail"],
+        "this API key doesn't have the `write` scope"
+    );
+    let response = send_as(app.clone(), token, Method::DELETE, "/person/1", None).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    // Nothing was deleted.
+    let response = send_as(app, token, Method::GET, "/person/1", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn write_scope_alone_cannot_read() {
+    let (app, token) = app_with_key(&[Scope::Write]).await;
+    let token = Some(token.as_str());
+
+    let body = json!({ "name": "Carol", "age": 30 });
+    let response = send_as(app.clone(), token, Method::POST, "/person", Some(body)).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let response = send_as(app, token, Method::GET, "/person/1", None).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn the_key_is_checked_before_the_body() {
+    let (app, _) = app_with_key(&[Scope::Write]).await;
+    let response = send_as(
+        app,
+        None,
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "" })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn other_routes_stay_public() {
+    let (app, _) = app_with_key(&[]).await;
+    let response = send_as(app, None, Method::GET, "/json/5", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn keys_are_stored_hashed() {
+    let pool = test_pool().await;
+    let token = auth::create_api_key(&pool, "hashed", &[Scope::Read])
+        .await
+        .unwrap();
+    assert_eq!(token.len(), 64);
+    assert_ne!(token, auth::generate_token());
+
+    let stored: Vec<(String,)> = sqlx::query_as("SELECT key_hash FROM api_keys")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 2);
+    assert!(stored.iter().all(|(hash,)| !hash.contains(&token)));
+}
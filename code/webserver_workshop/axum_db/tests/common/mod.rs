@@ -0,0 +1,12 @@
+// Each test file compiles this module separately, so not every file uses
+// every helper. The shared ones live in `axum_db::test_util`.
+#![allow(dead_code)]
+
+use axum::Router;
+
+pub use axum_db::test_util::*;
+
+/// The whole app, with the default config, over a fresh database.
+pub async fn test_app() -> Router {
+    axum_db::app(axum_db::AppState::new(test_pool().await))
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_db::AppState;
+use common::{get, test_app, test_pool};
+
+#[tokio::test]
+async fn existing_person_is_200() {
+    let response = get(test_app().await, "/person/1").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Alice");
+}
+
+#[tokio::test]
+async fn missing_person_is_404() {
+    let response = get(test_app().await, "/person/999").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(response.body["status"], 404);
+    assert_eq!(response.body["title"], "Not Found");
+}
+
+#[tokio::test]
+async fn bad_path_parameter_is_400() {
+    let response = get(test_app().await, "/person/bob").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(response.body["status"], 400);
+    assert!(response.body["detail"]
+        .as_str()
+        .unwrap()
+        .contains("Cannot parse"));
+
+    let response = get(test_app().await, "/json/-1").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn database_failure_is_500() {
+    let pool = test_pool().await;
+    sqlx::query("DROP TABLE my_data")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = get(axum_db::app(AppState::new(pool)), "/person/1").await;
+    assert_eq!(response.status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response.body["status"], 500);
+    // The underlying error is logged, not sent to the client.
+    assert!(!response.body["detail"]
+        .as_str()
+        .unwrap()
+        .contains("my_data"));
+}
@@ -0,0 +1,303 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, Response, StatusCode};
+use axum::Router;
+use axum_db::events::{Events, PersonEvent};
+use axum_db::{shutdown, AppState, Person};
+use common::{send, test_pool, TEST_TOKEN};
+use futures_util::{SinkExt, StreamExt};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tower::ServiceExt;
+
+async fn test_state() -> AppState {
+    AppState::new(test_pool().await)
+}
+
+async fn open_sse(app: Router, token: Option<&str>) -> Response<Body> {
+    let mut request = Request::builder().uri("/events");
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    app.oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+// Reads the next event off an SSE stream: its name, and its data as
+// JSON. `None` if the stream ended.
+async fn next_sse(body: &mut Body) -> Option<(String, Value)> {
+    let mut text = String::new();
+    while !text.ends_with("\n\n") {
+        let frame = body.frame().await?.unwrap();
+        text.push_str(std::str::from_utf8(frame.data_ref().unwrap()).unwrap());
+    }
+    let field = |name: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .unwrap()
+            .to_string()
+    };
+    Some((
+        field("event: "),
+        serde_json::from_str(&field("data: ")).unwrap(),
+    ))
+}
+
+#[tokio::test]
+async fn changes_are_streamed_as_server_sent_events() {
+    let app = axum_db::app(test_state().await);
+    let response = open_sse(app.clone(), Some(TEST_TOKEN)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/event-stream"
+    );
+    let mut body = response.into_body();
+
+    let created = send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Carol", "age": 30 })),
+    )
+    .await;
+    let id = created.body["id"].as_i64().unwrap();
+    let (name, data) = next_sse(&mut body).await.unwrap();
+    assert_eq!(name, "created");
+    assert_eq!(data, json!({ "type": "created", "person": created.body }));
+
+    let uri = format!("/person/{id}");
+    send(app.clone(), Method::PATCH, &uri, Some(json!({ "age": 31 }))).await;
+    let (name, data) = next_sse(&mut body).await.unwrap();
+    assert_eq!(name, "updated");
+    assert_eq!(data["person"]["age"], 31);
+
+    send(
+        app.clone(),
+        Method::PUT,
+        &uri,
+        Some(json!({ "name": "Caroline", "age": 32 })),
+    )
+    .await;
+    let (name, data) = next_sse(&mut body).await.unwrap();
+    assert_eq!(name, "updated");
+    assert_eq!(data["person"]["name"], "Caroline");
+
+    send(app, Method::DELETE, &uri, None).await;
+    let (name, data) = next_sse(&mut body).await.unwrap();
+    assert_eq!(name, "deleted");
+    assert_eq!(data, json!({ "type": "deleted", "id": id }));
+}
+
+#[tokio::test]
+async fn failed_changes_are_not_published() {
+    let state = test_state().await;
+    let mut subscription = state.events.subscribe();
+    let app = axum_db::app(state.clone());
+
+    let response = send(app.clone(), Method::DELETE, "/person/9999", None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    let response = send(
+        app,
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "", "age": 30 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // The next thing the subscription sees is this, not anything above.
+    state.events.publish(PersonEvent::Deleted { id: 1 });
+    assert_eq!(
+        subscription.next().await,
+        Some(PersonEvent::Deleted { id: 1 })
+    );
+}
+
+#[tokio::test]
+async fn the_streams_need_a_read_key() {
+    let app = axum_db::app(test_state().await);
+    let response = open_sse(app, None).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+fn person(id: i32) -> Person {
+    Person {
+        id,
+        name: format!("Person {id}"),
+        age: 40,
+    }
+}
+
+#[tokio::test]
+async fn a_subscriber_that_falls_behind_is_told_how_much_it_missed() {
+    let events = Events::new(2);
+    let mut subscription = events.subscribe();
+    for id in 1..=5 {
+        events.publish(PersonEvent::Updated { person: person(id) });
+    }
+
+    // Only the last two are still in the channel.
+    assert_eq!(
+        subscription.next().await,
+        Some(PersonEvent::Lagged { missed: 3 })
+    );
+    assert_eq!(
+        subscription.next().await,
+        Some(PersonEvent::Updated { person: person(4) })
+    );
+    assert_eq!(
+        subscription.next().await,
+        Some(PersonEvent::Updated { person: person(5) })
+    );
+
+    // And it carries on as normal.
+    events.publish(PersonEvent::Deleted { id: 4 });
+    assert_eq!(
+        subscription.next().await,
+        Some(PersonEvent::Deleted { id: 4 })
+    );
+}
+
+#[tokio::test]
+async fn a_lagging_sse_client_gets_a_lagged_event() {
+    let mut state = test_state().await;
+    state.events = Events::new(1);
+    let app = axum_db::app(state.clone());
+    let mut body = open_sse(app, Some(TEST_TOKEN)).await.into_body();
+
+    // Nobody's reading the stream yet.
+    for id in 1..=3 {
+        state
+            .events
+            .publish(PersonEvent::Updated { person: person(id) });
+    }
+
+    let (name, data) = next_sse(&mut body).await.unwrap();
+    assert_eq!(name, "lagged");
+    assert_eq!(data, json!({ "type": "lagged", "missed": 2 }));
+    let (name, data) = next_sse(&mut body).await.unwrap();
+    assert_eq!(name, "updated");
+    assert_eq!(data["person"]["id"], 3);
+}
+
+#[tokio::test]
+async fn closing_ends_the_sse_stream() {
+    let state = test_state().await;
+    let app = axum_db::app(state.clone());
+    let mut body = open_sse(app, Some(TEST_TOKEN)).await.into_body();
+
+    state.events.close();
+    assert_eq!(next_sse(&mut body).await, None);
+}
+
+// A real server on a random port - a WebSocket needs an actual
+// connection to upgrade. Sending on the returned channel shuts it down
+// the way `main` does.
+async fn start_server(
+    state: AppState,
+) -> (
+    String,
+    oneshot::Sender<()>,
+    tokio::task::JoinHandle<shutdown::Drain>,
+) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let events = state.events.clone();
+    let signal = async move {
+        let _ = stopped.await;
+        events.close();
+    };
+    let app = axum_db::app(state);
+    let server = tokio::spawn(async move {
+        shutdown::serve(listener, app, signal, Duration::from_secs(5))
+            .await
+            .unwrap()
+    });
+    (format!("ws://{address}/ws"), stop, server)
+}
+
+fn ws_request(url: &str, token: Option<&str>) -> tungstenite::handshake::client::Request {
+    let mut request = url.into_client_request().unwrap();
+    if let Some(token) = token {
+        let value = format!("Bearer {token}").parse().unwrap();
+        request.headers_mut().insert(header::AUTHORIZATION, value);
+    }
+    request
+}
+
+async fn next_json<S>(socket: &mut S) -> Value
+where
+    S: StreamExt<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    match socket.next().await.unwrap().unwrap() {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        other => panic!("expected a text message, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn changes_are_streamed_over_a_websocket() {
+    let state = test_state().await;
+    let app = axum_db::app(state.clone());
+    let (url, stop, server) = start_server(state).await;
+    let (mut socket, _) = tokio_tungstenite::connect_async(ws_request(&url, Some(TEST_TOKEN)))
+        .await
+        .unwrap();
+
+    let created = send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Dan", "age": 50 })),
+    )
+    .await;
+    assert_eq!(
+        next_json(&mut socket).await,
+        json!({ "type": "created", "person": created.body })
+    );
+
+    // What the client sends is ignored - but a ping still gets a pong.
+    socket
+        .send(Message::Text("hello?".to_string()))
+        .await
+        .unwrap();
+    socket.send(Message::Ping(b"ping".to_vec())).await.unwrap();
+    assert_eq!(
+        socket.next().await.unwrap().unwrap(),
+        Message::Pong(b"ping".to_vec())
+    );
+
+    let uri = format!("/person/{}", created.body["id"]);
+    send(app, Method::DELETE, &uri, None).await;
+    assert_eq!(next_json(&mut socket).await["type"], "deleted");
+
+    // Shutting down closes the socket politely, and doesn't have to wait
+    // for the drain timeout.
+    stop.send(()).unwrap();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Away),
+        other => panic!("expected a close frame, got {other:?}"),
+    }
+    assert_eq!(server.await.unwrap(), shutdown::Drain::Complete);
+}
+
+#[tokio::test]
+async fn the_websocket_needs_a_read_key() {
+    let (url, _stop, _server) = start_server(test_state().await).await;
+    match tokio_tungstenite::connect_async(ws_request(&url, None)).await {
+        Err(tungstenite::Error::Http(response)) => {
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED)
+        }
+        other => panic!("expected a 401, got {other:?}"),
+    }
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request, StatusCode};
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use axum_db::config::LimitsConfig;
+use axum_db::{limits, AppState, Config};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tower::ServiceExt;
+
+// Most of these tests pause tokio's clock: time only moves when we
+// `advance` it, or when every task is waiting on a timer. So a 60-second
+// request takes no time at all, and a token bucket refills exactly when
+// we say.
+
+// `/` answers straight away; `/slow` signals `started`, then takes a minute.
+fn limited(limits: LimitsConfig, started: Arc<Semaphore>) -> Router {
+    let router = Router::new().route("/", get(|| async { "ok" })).route(
+        "/slow",
+        get(move || async move {
+            started.add_permits(1);
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            "slow"
+        }),
+    );
+    limits::apply(router, &limits)
+}
+
+// A request as if it came over a socket from `ip`.
+async fn send_from(app: Router, ip: &str, uri: &str) -> Response {
+    let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let address = SocketAddr::new(ip.parse().unwrap(), 50_000);
+    request.extensions_mut().insert(ConnectInfo(address));
+    app.oneshot(request).await.unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn each_client_gets_its_own_bucket() {
+    let app = limited(
+        LimitsConfig {
+            rate_per_sec: 1,
+            burst: 2,
+            ..LimitsConfig::none()
+        },
+        Arc::new(Semaphore::new(0)),
+    );
+
+    // The burst...
+    assert_eq!(
+        send_from(app.clone(), "10.0.0.1", "/").await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        send_from(app.clone(), "10.0.0.1", "/").await.status(),
+        StatusCode::OK
+    );
+    // ...is used up.
+    let response = send_from(app.clone(), "10.0.0.1", "/").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "application/problem+json"
+    );
+
+    // Somebody else isn't affected.
+    assert_eq!(
+        send_from(app.clone(), "10.0.0.2", "/").await.status(),
+        StatusCode::OK
+    );
+
+    // A second later, there's another token - but only one.
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert_eq!(
+        send_from(app.clone(), "10.0.0.1", "/").await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        send_from(app, "10.0.0.1", "/").await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_wait_is_until_the_next_token() {
+    let limiter = limits::RateLimiter::new(4, 1);
+    let client = Some("10.0.0.1".parse().unwrap());
+    assert_eq!(limiter.check(client), Ok(()));
+    assert_eq!(limiter.check(client), Err(Duration::from_millis(250)));
+
+    tokio::time::advance(Duration::from_millis(100)).await;
+    assert_eq!(limiter.check(client), Err(Duration::from_millis(150)));
+    tokio::time::advance(Duration::from_millis(150)).await;
+    assert_eq!(limiter.check(client), Ok(()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_number_of_buckets_is_capped() {
+    // One token a second, so nobody's bucket fills up again in this test
+    // unless we move the clock on.
+    let limiter = limits::RateLimiter::new(1, 1);
+    let client = |n: usize| Some(std::net::Ipv4Addr::from(n as u32).into());
+
+    for n in 0..limits::MAX_BUCKETS + 500 {
+        assert_eq!(limiter.check(client(n)), Ok(()));
+    }
+    assert_eq!(limiter.clients(), limits::MAX_BUCKETS);
+    // The ones we saw most recently are still limited...
+    let newest = client(limits::MAX_BUCKETS + 499);
+    assert!(limiter.check(newest).is_err());
+    // ...and the ones we saw longest ago were forgotten.
+    assert_eq!(limiter.check(client(0)), Ok(()));
+    assert_eq!(limiter.clients(), limits::MAX_BUCKETS);
+
+    // Once their buckets have filled up, they're all as good as new.
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert_eq!(limiter.check(client(1_000_000)), Ok(()));
+    assert_eq!(limiter.clients(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn too_many_requests_at_once_are_shed() {
+    let started = Arc::new(Semaphore::new(0));
+    let app = limited(
+        LimitsConfig {
+            max_concurrent: 2,
+            ..LimitsConfig::none()
+        },
+        started.clone(),
+    );
+
+    let first = tokio::spawn(send_from(app.clone(), "10.0.0.1", "/slow"));
+    let second = tokio::spawn(send_from(app.clone(), "10.0.0.2", "/slow"));
+    started.acquire_many(2).await.unwrap().forget();
+
+    // Both slots are taken, so the third is turned away - immediately,
+    // rather than waiting a minute for a slot.
+    let response = send_from(app.clone(), "10.0.0.3", "/").await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+    assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+    assert_eq!(second.await.unwrap().status(), StatusCode::OK);
+    assert_eq!(
+        send_from(app, "10.0.0.3", "/").await.status(),
+        StatusCode::OK
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn slow_requests_time_out() {
+    let app = limited(
+        LimitsConfig {
+            request_timeout: Duration::from_secs(5),
+            ..LimitsConfig::none()
+        },
+        Arc::new(Semaphore::new(0)),
+    );
+
+    let start = Instant::now();
+    let response = send_from(app.clone(), "10.0.0.1", "/slow").await;
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    assert_eq!(start.elapsed(), Duration::from_secs(5));
+
+    assert_eq!(
+        send_from(app, "10.0.0.1", "/").await.status(),
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn the_app_applies_its_configured_limits() {
+    let config = Config {
+        limits: LimitsConfig {
+            rate_per_sec: 1,
+            burst: 1,
+            ..LimitsConfig::none()
+        },
+        ..Config::default()
+    };
+    let state = AppState::with_config(common::test_pool().await, config);
+    let app = axum_db::app(state.clone());
+
+    assert_eq!(
+        common::get(app.clone(), "/json/1").await.status,
+        StatusCode::OK
+    );
+    let response = common::get(app, "/json/1").await;
+    assert_eq!(response.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.body["status"], 429);
+
+    // Requests we turn away still show up in the metrics.
+    let metrics = state.metrics.encode();
+    assert!(
+        metrics.contains(r#"http_requests_total{method="GET",route="/json/:n",status="429"} 1"#)
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use common::{get, test_app};
+use http_body_util::BodyExt;
+use std::collections::HashMap;
+use tower::ServiceExt;
+
+// Scrapes `/metrics`, and parses the text exposition format into
+// `series -> value`, e.g. `http_requests_total{method="GET",...}` -> 1.0.
+async fn scrape(app: Router) -> HashMap<String, f64> {
+    let request = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+
+    String::from_utf8(bytes.to_vec())
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.is_empty())
+        .map(|line| {
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            (series.to_string(), value.parse().unwrap())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn requests_are_counted_by_route_and_status() {
+    let app = test_app().await;
+    get(app.clone(), "/person/1").await;
+    get(app.clone(), "/person/2").await;
+    get(app.clone(), "/person/999").await;
+    get(app.clone(), "/nope").await;
+
+    let metrics = scrape(app).await;
+    assert_eq!(
+        metrics[r#"http_requests_total{method="GET",route="/person/:id",status="200"}"#],
+        2.0
+    );
+    assert_eq!(
+        metrics[r#"http_requests_total{method="GET",route="/person/:id",status="404"}"#],
+        1.0
+    );
+    assert_eq!(
+        metrics[r#"http_requests_total{method="GET",route="unmatched",status="404"}"#],
+        1.0
+    );
+    assert_eq!(
+        metrics[r#"http_request_duration_seconds_count{method="GET",route="/person/:id",status="200"}"#],
+        2.0
+    );
+    assert_eq!(
+        metrics[r#"http_request_duration_seconds_bucket{method="GET",route="/person/:id",status="200",le="+Inf"}"#],
+        2.0
+    );
+}
+
+#[tokio::test]
+async fn pool_utilization_is_reported() {
+    let metrics = scrape(test_app().await).await;
+    // `test_pool` keeps exactly one connection open.
+    assert_eq!(metrics["db_pool_connections"], 1.0);
+    assert_eq!(metrics["db_pool_max_connections"], 1.0);
+    assert!(metrics["db_pool_idle_connections"] <= 1.0);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum_db::openapi::ApiDoc;
+use common::{get, test_app};
+use http_body_util::BodyExt;
+use std::path::Path;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+use workshop_util::testing::assert_snapshot;
+
+// The whole OpenAPI document is checked in. If it changes on purpose,
+// regenerate it with:
+//
+//   UPDATE_SNAPSHOTS=1 cargo test -p axum_db --test openapi
+#[test]
+fn the_spec_matches_the_snapshot() {
+    let spec = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+    let snapshot = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/openapi.json");
+    assert_snapshot(&snapshot, &spec);
+}
+
+#[tokio::test]
+async fn the_spec_is_served() {
+    let response = get(test_app().await, "/openapi.json").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body,
+        serde_json::to_value(ApiDoc::openapi()).unwrap()
+    );
+    assert_eq!(
+        response.body["paths"]["/person/{id}"]["get"]["security"][0]["api_key"][0],
+        "read"
+    );
+    assert!(response.body["components"]["schemas"]["Person"].is_object());
+}
+
+#[tokio::test]
+async fn the_docs_page_lists_the_routes() {
+    let request = Request::builder().uri("/docs").body(Body::empty()).unwrap();
+    let response = test_app().await.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/html"));
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body = String::from_utf8(bytes.to_vec()).unwrap();
+
+    assert!(body.contains("<td><code>/person/{id}</code></td>"));
+    assert!(body.contains("Change some of a person&#x27;s fields."));
+    assert!(body.contains(r#"<h3 id="PersonPatch">PersonPatch</h3>"#));
+}
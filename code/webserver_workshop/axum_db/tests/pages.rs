@@ -0,0 +1,135 @@
+mod common;
+
+use askama::Template;
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum::response::IntoResponse;
+use axum::Router;
+use axum_db::auth::{self, Scope};
+use common::{get, send, send_as, test_app, test_pool, TEST_TOKEN};
+use http_body_util::BodyExt;
+use serde_json::json;
+use tower::ServiceExt;
+
+async fn get_html(app: Router, uri: &str) -> (StatusCode, String) {
+    get_html_as(app, TEST_TOKEN, uri).await
+}
+
+async fn get_html_as(app: Router, token: &str, uri: &str) -> (StatusCode, String) {
+    let request = Request::builder()
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/html"));
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn root_says_hello() {
+    let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+    let response = test_app().await.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&bytes[..], b"Hello, World!");
+}
+
+#[tokio::test]
+async fn hello_page_uses_the_layout() {
+    let (status, body) = get_html(test_app().await, "/hello/42").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("You picked 42"));
+    assert!(body.contains("<title>Hello</title>"));
+    assert!(body.contains(r#"<a href="/people">People</a>"#));
+}
+
+#[tokio::test]
+async fn people_page_needs_a_read_key() {
+    let pool = test_pool().await;
+    let token = auth::create_api_key(&pool, "reader", &[Scope::Read])
+        .await
+        .unwrap();
+    let app = axum_db::app(axum_db::AppState::new(pool));
+
+    let response = send_as(app.clone(), None, Method::GET, "/people", None).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = get_html_as(app, &token, "/people").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<td>Alice</td><td>42</td>"));
+    assert!(body.contains("<td>Bob</td><td>69</td>"));
+    assert!(!body.contains("Next"));
+}
+
+#[tokio::test]
+async fn people_page_links_to_other_pages() {
+    let config = axum_db::Config {
+        default_per_page: 1,
+        ..Default::default()
+    };
+    let app = axum_db::app(axum_db::AppState::with_config(test_pool().await, config));
+
+    let (_, body) = get_html(app.clone(), "/people").await;
+    assert!(body.contains("Alice") && !body.contains("Bob"));
+    assert!(body.contains(r#"<a href="/people?page=2">Next</a>"#));
+
+    let (_, body) = get_html(app, "/people?page=2").await;
+    assert!(body.contains("Bob"));
+    assert!(body.contains(r#"<a href="/people?page=1">Previous</a>"#));
+    assert!(!body.contains("Next"));
+}
+
+#[tokio::test]
+async fn people_page_escapes_names() {
+    let app = test_app().await;
+    let name = "<script>alert(1)</script>";
+    send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": name, "age": 1 })),
+    )
+    .await;
+
+    let (_, body) = get_html(app, "/people").await;
+    assert!(!body.contains(name));
+    assert!(body.contains("&lt;script&gt;"));
+}
+
+// A value whose `Display` fails, as a stand-in for any rendering error.
+struct Broken;
+
+impl std::fmt::Display for Broken {
+    fn fmt(&self, _: &mut std::fmt::Formatter) -> std::fmt::Result {
+        Err(std::fmt::Error)
+    }
+}
+
+#[derive(Template)]
+#[template(source = "<p>{{ value }}</p>", ext = "html")]
+struct BrokenTemplate {
+    value: Broken,
+}
+
+#[tokio::test]
+async fn render_errors_are_500() {
+    let response = axum_db::render(&BrokenTemplate { value: Broken }).into_response();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "application/problem+json"
+    );
+}
+
+#[tokio::test]
+async fn json_returns_alice() {
+    let response = get(test_app().await, "/json/7").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body, json!({ "name": "Alice", "age": 7 }));
+}
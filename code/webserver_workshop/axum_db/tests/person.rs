@@ -0,0 +1,209 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum_db::{AppState, Config};
+use common::{get, send, test_app, test_pool, TEST_TOKEN};
+use serde_json::json;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn create_returns_201_with_location() {
+    let app = test_app().await;
+
+    let response = send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Carol", "age": 30 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.headers[header::LOCATION], "/person/3");
+    assert_eq!(
+        response.body,
+        json!({ "id": 3, "name": "Carol", "age": 30 })
+    );
+
+    let response = get(app, "/person/3").await;
+    assert_eq!(response.body["name"], "Carol");
+}
+
+#[tokio::test]
+async fn invalid_fields_are_422_with_every_error() {
+    let app = test_app().await;
+
+    let response = send(
+        app,
+        Method::POST,
+        "/person",
+        Some(json!({ "name": " ", "age": 200 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let fields: Vec<&str> = response.body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["name", "age"]);
+}
+
+#[tokio::test]
+async fn missing_or_mistyped_fields_are_422() {
+    let app = test_app().await;
+
+    let response = send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Dave" })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["errors"][0]["field"], "body");
+    assert!(response.body["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("missing field `age`"));
+
+    let response = send(
+        app,
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Dave", "age": "old" })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn malformed_json_is_400() {
+    let app = test_app().await;
+
+    let send_raw = |content_type: &'static str, body: &'static str| {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/person")
+            .header(header::AUTHORIZATION, format!("Bearer {TEST_TOKEN}"))
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap();
+        app.clone().oneshot(request)
+    };
+    let response = send_raw("application/json", r#"{"name": "Dave", "#)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = send_raw("text/plain", r#"{"name": "Dave", "age": 30}"#)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn put_replaces_and_patch_updates() {
+    let app = test_app().await;
+
+    let response = send(
+        app.clone(),
+        Method::PUT,
+        "/person/1",
+        Some(json!({ "name": "Alicia", "age": 43 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body,
+        json!({ "id": 1, "name": "Alicia", "age": 43 })
+    );
+
+    let response = send(
+        app.clone(),
+        Method::PATCH,
+        "/person/1",
+        Some(json!({ "age": 44 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        response.body,
+        json!({ "id": 1, "name": "Alicia", "age": 44 })
+    );
+
+    let response = send(app.clone(), Method::PATCH, "/person/1", Some(json!({}))).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = send(
+        app,
+        Method::PUT,
+        "/person/999",
+        Some(json!({ "name": "Nobody", "age": 1 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_removes_the_person() {
+    let app = test_app().await;
+
+    let response = send(app.clone(), Method::DELETE, "/person/2", None).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = get(app.clone(), "/person/2").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = send(app, Method::DELETE, "/person/2", None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn list_pages_and_filters() {
+    let app = test_app().await;
+    for (name, age) in [("Alan", 42), ("Carol", 30), ("Dave", 42)] {
+        send(
+            app.clone(),
+            Method::POST,
+            "/person",
+            Some(json!({ "name": name, "age": age })),
+        )
+        .await;
+    }
+
+    let response = get(app.clone(), "/person?per_page=2&page=2").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["total"], 5);
+    assert_eq!(response.body["page"], 2);
+    let names: Vec<&str> = response.body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|person| person["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Alan", "Carol"]);
+
+    let response = get(app.clone(), "/person?name=Al&age=42").await;
+    assert_eq!(response.body["total"], 2);
+
+    let response = get(app, "/person?per_page=1000").await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.body["errors"][0]["field"], "per_page");
+}
+
+#[tokio::test]
+async fn page_limits_come_from_the_config() {
+    let config = Config {
+        default_per_page: 1,
+        max_per_page: 2,
+        ..Default::default()
+    };
+    let app = axum_db::app(AppState::with_config(test_pool().await, config));
+
+    let response = get(app.clone(), "/person").await;
+    assert_eq!(response.body["items"].as_array().unwrap().len(), 1);
+
+    let response = get(app, "/person?per_page=3").await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,325 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_db::repository::{MemoryRepository, PersonFilter, SqliteRepository};
+use axum_db::{AppState, NewPerson, Person, PersonPatch, PersonRepository};
+use common::{get, test_pool};
+use std::sync::Arc;
+
+// Every backend has to pass the same tests. `conformance!` writes one
+// `#[tokio::test]` per backend per case below, e.g. `sqlite::filters` and
+// `memory::filters`.
+//
+// The Postgres ones only exist with `--features postgres`, and only run
+// if `TEST_POSTGRES_URL` points at a database we can create schemas in:
+//
+//   TEST_POSTGRES_URL=postgres://localhost/postgres cargo test -p axum_db --features postgres --test repository
+//
+// Each case starts with nobody in the repository.
+
+macro_rules! conformance {
+    ($($case:ident),* $(,)?) => {
+        mod sqlite {
+            $(
+                #[tokio::test]
+                async fn $case() {
+                    super::$case(super::empty_sqlite().await).await;
+                }
+            )*
+        }
+
+        mod memory {
+            $(
+                #[tokio::test]
+                async fn $case() {
+                    super::$case(std::sync::Arc::new(axum_db::repository::MemoryRepository::new())).await;
+                }
+            )*
+        }
+
+        #[cfg(feature = "postgres")]
+        mod postgres {
+            $(
+                #[tokio::test]
+                async fn $case() {
+                    if let Some(people) = super::empty_postgres(stringify!($case)).await {
+                        super::$case(people).await;
+                    }
+                }
+            )*
+        }
+    };
+}
+
+conformance!(
+    new_people_get_new_ids,
+    missing_people_are_none,
+    lists_are_in_id_order_and_paged,
+    filters,
+    wildcards_in_names_are_literal,
+    replace_changes_everything,
+    update_changes_only_what_it_is_given,
+    delete_removes_them_for_good,
+);
+
+async fn empty_sqlite() -> Arc<dyn PersonRepository> {
+    let pool = test_pool().await;
+    sqlx::query("DELETE FROM my_data")
+        .execute(&pool)
+        .await
+        .unwrap();
+    Arc::new(SqliteRepository::new(pool))
+}
+
+// A schema of its own for each test, so they can run at the same time.
+#[cfg(feature = "postgres")]
+async fn empty_postgres(case: &str) -> Option<Arc<dyn PersonRepository>> {
+    use axum_db::repository::PostgresRepository;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use sqlx::Connection;
+    use std::str::FromStr;
+
+    let Ok(url) = std::env::var("TEST_POSTGRES_URL") else {
+        eprintln!("skipping the Postgres version of `{case}`: TEST_POSTGRES_URL isn't set");
+        return None;
+    };
+    let schema = format!("axum_db_test_{case}");
+    let mut connection = sqlx::PgConnection::connect(&url).await.unwrap();
+    sqlx::query(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+        .execute(&mut connection)
+        .await
+        .unwrap();
+    sqlx::query(&format!("CREATE SCHEMA {schema}"))
+        .execute(&mut connection)
+        .await
+        .unwrap();
+
+    let options = PgConnectOptions::from_str(&url)
+        .unwrap()
+        .options([("search_path", schema.as_str())]);
+    let pool = PgPoolOptions::new().connect_with(options).await.unwrap();
+    let people = PostgresRepository::new(pool.clone()).await.unwrap();
+    sqlx::query("DELETE FROM my_data")
+        .execute(&pool)
+        .await
+        .unwrap();
+    Some(Arc::new(people))
+}
+
+fn new_person(name: &str, age: i32) -> NewPerson {
+    NewPerson {
+        name: name.to_string(),
+        age,
+    }
+}
+
+async fn add(people: &Arc<dyn PersonRepository>, name: &str, age: i32) -> Person {
+    people.create(&new_person(name, age)).await.unwrap()
+}
+
+fn names(people: &[Person]) -> Vec<&str> {
+    people.iter().map(|person| person.name.as_str()).collect()
+}
+
+async fn new_people_get_new_ids(people: Arc<dyn PersonRepository>) {
+    let carol = add(&people, "Carol", 30).await;
+    let dan = add(&people, "Dan", 40).await;
+    assert_eq!(carol.name, "Carol");
+    assert_eq!(carol.age, 30);
+    assert!(dan.id > carol.id);
+
+    assert_eq!(people.get(carol.id).await.unwrap(), Some(carol));
+    assert_eq!(people.get(dan.id).await.unwrap(), Some(dan));
+}
+
+async fn missing_people_are_none(people: Arc<dyn PersonRepository>) {
+    assert_eq!(people.get(9999).await.unwrap(), None);
+    assert_eq!(
+        people
+            .replace(9999, &new_person("Nobody", 1))
+            .await
+            .unwrap(),
+        None
+    );
+    let patch = PersonPatch {
+        name: None,
+        age: Some(1),
+    };
+    assert_eq!(people.update(9999, &patch).await.unwrap(), None);
+    assert!(!people.delete(9999).await.unwrap());
+}
+
+async fn lists_are_in_id_order_and_paged(people: Arc<dyn PersonRepository>) {
+    // Not in name order, so sorting by the wrong thing shows up.
+    for name in ["Zoe", "Yan", "Xia", "Wes", "Val"] {
+        add(&people, name, 20).await;
+    }
+    let everyone = PersonFilter::default();
+
+    let all = people.list(&everyone, 100, 0).await.unwrap();
+    assert_eq!(names(&all), ["Zoe", "Yan", "Xia", "Wes", "Val"]);
+    assert_eq!(people.count(&everyone).await.unwrap(), 5);
+
+    assert_eq!(
+        names(&people.list(&everyone, 2, 0).await.unwrap()),
+        ["Zoe", "Yan"]
+    );
+    assert_eq!(
+        names(&people.list(&everyone, 2, 2).await.unwrap()),
+        ["Xia", "Wes"]
+    );
+    assert_eq!(names(&people.list(&everyone, 2, 4).await.unwrap()), ["Val"]);
+    assert!(people.list(&everyone, 2, 6).await.unwrap().is_empty());
+}
+
+async fn filters(people: Arc<dyn PersonRepository>) {
+    add(&people, "Alice", 42).await;
+    add(&people, "Malik", 42).await;
+    add(&people, "Bob", 42).await;
+    add(&people, "Alison", 19).await;
+
+    let by_name = PersonFilter {
+        name: Some("ALI".to_string()),
+        age: None,
+    };
+    assert_eq!(
+        names(&people.list(&by_name, 100, 0).await.unwrap()),
+        ["Alice", "Malik", "Alison"]
+    );
+    assert_eq!(people.count(&by_name).await.unwrap(), 3);
+
+    let by_age = PersonFilter {
+        name: None,
+        age: Some(42),
+    };
+    assert_eq!(
+        names(&people.list(&by_age, 100, 0).await.unwrap()),
+        ["Alice", "Malik", "Bob"]
+    );
+    assert_eq!(people.count(&by_age).await.unwrap(), 3);
+
+    let both = PersonFilter {
+        name: Some("ali".to_string()),
+        age: Some(19),
+    };
+    assert_eq!(
+        names(&people.list(&both, 100, 0).await.unwrap()),
+        ["Alison"]
+    );
+    assert_eq!(people.count(&both).await.unwrap(), 1);
+
+    let nobody = PersonFilter {
+        name: Some("Zed".to_string()),
+        age: None,
+    };
+    assert!(people.list(&nobody, 100, 0).await.unwrap().is_empty());
+    assert_eq!(people.count(&nobody).await.unwrap(), 0);
+}
+
+async fn wildcards_in_names_are_literal(people: Arc<dyn PersonRepository>) {
+    add(&people, "100% Sam", 30).await;
+    add(&people, "Sam_2", 30).await;
+    add(&people, r"Back\slash", 30).await;
+    add(&people, "Samuel", 30).await;
+
+    let search = |text: &str| PersonFilter {
+        name: Some(text.to_string()),
+        age: None,
+    };
+    assert_eq!(
+        names(&people.list(&search("%"), 100, 0).await.unwrap()),
+        ["100% Sam"]
+    );
+    assert_eq!(
+        names(&people.list(&search("_"), 100, 0).await.unwrap()),
+        ["Sam_2"]
+    );
+    assert_eq!(
+        names(&people.list(&search(r"\"), 100, 0).await.unwrap()),
+        [r"Back\slash"]
+    );
+    assert_eq!(people.count(&search("Sam")).await.unwrap(), 3);
+}
+
+async fn replace_changes_everything(people: Arc<dyn PersonRepository>) {
+    let carol = add(&people, "Carol", 30).await;
+    let other = add(&people, "Other", 50).await;
+
+    let replaced = people
+        .replace(carol.id, &new_person("Caroline", 31))
+        .await
+        .unwrap();
+    let expected = Person {
+        id: carol.id,
+        name: "Caroline".to_string(),
+        age: 31,
+    };
+    assert_eq!(replaced, Some(expected.clone()));
+    assert_eq!(people.get(carol.id).await.unwrap(), Some(expected));
+    assert_eq!(people.get(other.id).await.unwrap(), Some(other));
+}
+
+async fn update_changes_only_what_it_is_given(people: Arc<dyn PersonRepository>) {
+    let carol = add(&people, "Carol", 30).await;
+
+    let older = PersonPatch {
+        name: None,
+        age: Some(31),
+    };
+    let updated = people.update(carol.id, &older).await.unwrap().unwrap();
+    assert_eq!((updated.name.as_str(), updated.age), ("Carol", 31));
+
+    let renamed = PersonPatch {
+        name: Some("Caz".to_string()),
+        age: None,
+    };
+    let updated = people.update(carol.id, &renamed).await.unwrap().unwrap();
+    assert_eq!((updated.name.as_str(), updated.age), ("Caz", 31));
+    assert_eq!(people.get(carol.id).await.unwrap(), Some(updated));
+}
+
+async fn delete_removes_them_for_good(people: Arc<dyn PersonRepository>) {
+    let carol = add(&people, "Carol", 30).await;
+    let dan = add(&people, "Dan", 40).await;
+
+    assert!(people.delete(dan.id).await.unwrap());
+    assert_eq!(people.get(dan.id).await.unwrap(), None);
+    assert!(!people.delete(dan.id).await.unwrap());
+    assert_eq!(people.count(&PersonFilter::default()).await.unwrap(), 1);
+
+    // The newest id isn't handed out again.
+    let erin = add(&people, "Erin", 50).await;
+    assert!(erin.id > dan.id);
+    assert_eq!(people.get(carol.id).await.unwrap(), Some(carol));
+}
+
+// The handlers don't care which repository they're given.
+#[tokio::test]
+async fn the_app_can_keep_people_in_memory() {
+    let pool = test_pool().await;
+    let state = AppState {
+        people: Arc::new(MemoryRepository::seeded()),
+        ..AppState::new(pool.clone())
+    };
+    let app = axum_db::app(state);
+
+    let response = get(app.clone(), "/person/2").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Bob");
+    let response = common::send(
+        app,
+        axum::http::Method::POST,
+        "/person",
+        Some(serde_json::json!({ "name": "Carol", "age": 30 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    assert_eq!(response.body["id"], 3);
+
+    // The SQLite table never saw Carol.
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM my_data")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
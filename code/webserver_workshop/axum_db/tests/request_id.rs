@@ -0,0 +1,31 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use tower::ServiceExt;
+
+mod common;
+
+#[tokio::test]
+async fn a_request_id_is_generated() {
+    let app = common::test_app().await;
+    let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let id = response.headers()["x-request-id"].to_str().unwrap();
+    assert_eq!(id.len(), 36, "expected a UUID, got {id}");
+}
+
+#[tokio::test]
+async fn the_clients_request_id_is_kept() {
+    let app = common::test_app().await;
+    let request = Request::builder()
+        .uri("/person/999")
+        .header("authorization", format!("Bearer {}", common::TEST_TOKEN))
+        .header("x-request-id", "abc-123")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["x-request-id"], "abc-123");
+}
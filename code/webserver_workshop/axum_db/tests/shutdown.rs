@@ -0,0 +1,84 @@
+mod common;
+
+use axum::routing::get;
+use axum_db::shutdown::{self, Drain};
+use axum_db::AppState;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Notify};
+
+// The real app, plus a `/slow` route that tells us when it has started
+// and then takes `delay` to answer.
+fn slow_app(pool: SqlitePool, started: Arc<Notify>, delay: Duration) -> axum::Router {
+    axum_db::app(AppState::new(pool)).route(
+        "/slow",
+        get(move || async move {
+            started.notify_one();
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    )
+}
+
+#[tokio::test]
+async fn requests_in_flight_finish_before_shutdown() {
+    let pool = common::test_pool().await;
+    let started = Arc::new(Notify::new());
+    let app = slow_app(pool.clone(), started.clone(), Duration::from_millis(300));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(shutdown::serve(
+        listener,
+        app,
+        async move {
+            let _ = stopped.await;
+        },
+        Duration::from_secs(10),
+    ));
+
+    let request = tokio::spawn(reqwest::get(format!("http://{address}/slow")));
+    started.notified().await;
+    stop.send(()).unwrap();
+
+    // The slow request still gets its answer...
+    let response = request.await.unwrap().unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "done");
+
+    // ...and then the server stops, and stops listening.
+    assert_eq!(server.await.unwrap().unwrap(), Drain::Complete);
+    assert!(reqwest::get(format!("http://{address}/")).await.is_err());
+
+    shutdown::close_pool(&pool, Drain::Complete).await;
+    assert!(pool.is_closed());
+}
+
+#[tokio::test]
+async fn the_drain_timeout_gives_up_on_stuck_requests() {
+    let pool = common::test_pool().await;
+    let started = Arc::new(Notify::new());
+    let app = slow_app(pool, started.clone(), Duration::from_secs(3600));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(shutdown::serve(
+        listener,
+        app,
+        async move {
+            let _ = stopped.await;
+        },
+        Duration::from_millis(100),
+    ));
+
+    let _stuck = tokio::spawn(reqwest::get(format!("http://{address}/slow")));
+    started.notified().await;
+    stop.send(()).unwrap();
+
+    let drain = tokio::time::timeout(Duration::from_secs(5), server).await;
+    assert_eq!(drain.unwrap().unwrap().unwrap(), Drain::TimedOut);
+}
@@ -0,0 +1,288 @@
+use axum_db::{AppError, Metrics, Person};
+use prometheus::{IntCounter, IntGauge};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
+
+/// A bounded, expiring cache of people.
+///
+/// * Entries older than `ttl` are treated as missing (and dropped).
+/// * Once there are `capacity` entries, adding another evicts the one
+///   that was used least recently.
+/// * The mutating routes call `invalidate`, so (TTL aside) the cache
+///   never serves a row that has since changed through this server.
+/// * `get_or_load` makes concurrent misses for the same id share a single
+///   load ("single-flight"), instead of each running its own query.
+pub struct PersonCache {
+    inner: tokio::sync::Mutex<Inner>,
+    capacity: usize,
+    ttl: Duration,
+    // Prometheus counters are just atomics with a name, so they double as
+    // our statistics.
+    hits: IntCounter,
+    misses: IntCounter,
+    expirations: IntCounter,
+    evictions: IntCounter,
+    invalidations: IntCounter,
+    loads: IntCounter,
+    coalesced: IntCounter,
+    size: IntGauge,
+}
+
+fn counter(name: &str, help: &str) -> IntCounter {
+    IntCounter::new(format!("person_cache_{name}_total"), help).unwrap()
+}
+
+// One in-progress load. Everyone who misses on the same id while it's
+// running awaits the same cell.
+type Flight = Arc<OnceCell<Result<Person, AppError>>>;
+
+struct Entry {
+    person: Person,
+    expires: Instant,
+    // The `recency` key this entry is currently filed under.
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<i32, Entry>,
+    // Orders the ids from least to most recently used. Every access files
+    // the id under a new, larger tick - so the first key is the LRU entry.
+    recency: BTreeMap<u64, i32>,
+    tick: u64,
+    // Lives under the same lock as `entries`, so "is it cached?" and "is
+    // someone already loading it?" are answered together.
+    in_flight: HashMap<i32, Flight>,
+}
+
+impl Inner {
+    fn touch(&mut self, id: i32) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(&id) {
+            self.recency.remove(&entry.last_used);
+            self.recency.insert(tick, id);
+            entry.last_used = tick;
+        }
+    }
+
+    fn remove(&mut self, id: i32) -> Option<Entry> {
+        let entry = self.entries.remove(&id)?;
+        self.recency.remove(&entry.last_used);
+        Some(entry)
+    }
+}
+
+/// A snapshot of the cache counters, served by `GET /cache/stats`.
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub expirations: u64,
+    pub evictions: u64,
+    pub invalidations: u64,
+    /// How many times `get_or_load` actually ran its loader.
+    pub loads: u64,
+    /// Misses that waited for somebody else's load instead.
+    pub coalesced: u64,
+    pub size: usize,
+    pub capacity: usize,
+    pub ttl_seconds: f64,
+}
+
+impl PersonCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Arc<Self> {
+        assert!(capacity > 0, "cache capacity must be at least 1");
+        Arc::new(Self {
+            inner: tokio::sync::Mutex::new(Inner::default()),
+            capacity,
+            ttl,
+            hits: counter("hits", "Lookups answered from the cache"),
+            misses: counter("misses", "Lookups the cache couldn't answer"),
+            expirations: counter("expirations", "Entries dropped because they were too old"),
+            evictions: counter("evictions", "Entries dropped to make room"),
+            invalidations: counter(
+                "invalidations",
+                "Entries dropped because the row changed or was deleted",
+            ),
+            loads: counter("loads", "Database loads run on a miss"),
+            coalesced: counter("coalesced", "Misses that shared another request's load"),
+            size: IntGauge::new("person_cache_size", "People currently cached").unwrap(),
+        })
+    }
+
+    /// Adds the cache's counters, and its size, to `/metrics`.
+    pub fn register_metrics(&self, metrics: &Metrics) {
+        for counter in [
+            &self.hits,
+            &self.misses,
+            &self.expirations,
+            &self.evictions,
+            &self.invalidations,
+            &self.loads,
+            &self.coalesced,
+        ] {
+            metrics.register(counter.clone());
+        }
+        metrics.register(self.size.clone());
+        let capacity = IntGauge::new(
+            "person_cache_capacity",
+            "The most people the cache will hold",
+        )
+        .unwrap();
+        capacity.set(self.capacity as i64);
+        metrics.register(capacity);
+    }
+
+    /// Inserts (or replaces) a person, evicting the least recently used
+    /// entry if the cache is full.
+    ///
+    /// Not for the rows a write handler gets back: two writes to the same
+    /// id can reach here in the opposite order to the database, and then
+    /// the older row would stay cached. They `invalidate` instead.
+    pub async fn add(&self, person: Person) {
+        let mut inner = self.inner.lock().await;
+        self.insert(&mut inner, person);
+    }
+
+    fn insert(&self, inner: &mut Inner, person: Person) {
+        let id = person.id;
+        inner.remove(id);
+        // A load that started before this write would be stale - make sure
+        // it doesn't overwrite us when it finishes.
+        inner.in_flight.remove(&id);
+        while inner.entries.len() >= self.capacity {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+            self.evictions.inc();
+        }
+        inner.entries.insert(
+            id,
+            Entry {
+                person,
+                expires: Instant::now() + self.ttl,
+                last_used: 0,
+            },
+        );
+        inner.touch(id);
+        self.size.set(inner.entries.len() as i64);
+    }
+
+    pub async fn get(&self, id: i32) -> Option<Person> {
+        let mut inner = self.inner.lock().await;
+        self.lookup(&mut inner, id)
+    }
+
+    fn lookup(&self, inner: &mut Inner, id: i32) -> Option<Person> {
+        match inner.entries.get(&id) {
+            Some(entry) if entry.expires > Instant::now() => {
+                let person = entry.person.clone();
+                inner.touch(id);
+                self.hits.inc();
+                Some(person)
+            }
+            Some(_) => {
+                inner.remove(id);
+                self.size.set(inner.entries.len() as i64);
+                self.expirations.inc();
+                self.misses.inc();
+                None
+            }
+            None => {
+                self.misses.inc();
+                None
+            }
+        }
+    }
+
+    /// Returns the cached person, or runs `load` to fetch them. If another
+    /// request is already loading the same id, waits for its result
+    /// instead - so a burst of misses costs one query, not one each.
+    pub async fn get_or_load<F, Fut>(&self, id: i32, load: F) -> Result<Person, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Person, AppError>>,
+    {
+        // The request span has an empty `cache` field for us to fill in.
+        let span = tracing::Span::current();
+        let flight = {
+            let mut inner = self.inner.lock().await;
+            if let Some(person) = self.lookup(&mut inner, id) {
+                span.record("cache", "hit");
+                return Ok(person);
+            }
+            // Either nobody is loading this id, or we join their flight.
+            // A flight whose loader was cancelled is never finished, so
+            // it's safe to join: the next waiter's loader takes over.
+            inner.in_flight.entry(id).or_default().clone()
+        };
+
+        // `get_or_init` runs exactly one loader at a time. The winning
+        // loader files the result under the same lock that retires the
+        // flight - so a miss that comes along afterwards sees the entry,
+        // never a finished flight, and can't start a second load.
+        let loaded = AtomicBool::new(false);
+        let result = flight
+            .get_or_init(|| async {
+                loaded.store(true, Ordering::Relaxed);
+                self.loads.inc();
+                let result = load().await;
+                let mut inner = self.inner.lock().await;
+                // Unless a write replaced our flight while we were waiting
+                // on the database - then what we loaded is already stale.
+                if inner
+                    .in_flight
+                    .get(&id)
+                    .is_some_and(|current| Arc::ptr_eq(current, &flight))
+                {
+                    inner.in_flight.remove(&id);
+                    if let Ok(person) = &result {
+                        self.insert(&mut inner, person.clone());
+                    }
+                }
+                result
+            })
+            .await
+            .clone();
+        if loaded.load(Ordering::Relaxed) {
+            span.record("cache", "miss");
+        } else {
+            self.coalesced.inc();
+            span.record("cache", "coalesced");
+        }
+        result
+    }
+
+    /// Forgets a person - call this when the row changes or is deleted.
+    pub async fn invalidate(&self, id: i32) {
+        let mut inner = self.inner.lock().await;
+        inner.in_flight.remove(&id);
+        if inner.remove(id).is_some() {
+            self.size.set(inner.entries.len() as i64);
+            self.invalidations.inc();
+        }
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        let size = self.inner.lock().await.entries.len();
+        CacheStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            expirations: self.expirations.get(),
+            evictions: self.evictions.get(),
+            invalidations: self.invalidations.get(),
+            loads: self.loads.get(),
+            coalesced: self.coalesced.get(),
+            size,
+            capacity: self.capacity,
+            ttl_seconds: self.ttl.as_secs_f64(),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use axum_db::config::{read_file, ConfigError, FileConfig, ServerArgs};
+use axum_db::Config;
+use std::fmt;
+use std::time::Duration;
+
+// The same settings as `axum_db` (see `axum_db::config` for where they
+// come from), plus a `[cache]` section.
+
+#[derive(clap::Parser, Debug, Default)]
+pub struct Args {
+    #[command(flatten)]
+    pub server: ServerArgs,
+    /// How many people to keep in the cache.
+    #[arg(long, env = "CACHE_CAPACITY")]
+    pub cache_capacity: Option<usize>,
+    /// How long a cached person stays fresh.
+    #[arg(long, env = "CACHE_TTL_SECS")]
+    pub cache_ttl_secs: Option<u64>,
+}
+
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct CacheFile {
+    #[serde(flatten)]
+    pub server: FileConfig,
+    pub cache: CacheFileConfig,
+}
+
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct CacheFileConfig {
+    pub capacity: Option<usize>,
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub capacity: usize,
+    pub ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1_000,
+            ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+impl fmt::Display for CacheConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<30} = {}", "cache.capacity", self.capacity)?;
+        writeln!(f, "{:<30} = {}", "cache.ttl_secs", self.ttl.as_secs())
+    }
+}
+
+/// Loads and validates both the server and the cache settings.
+pub fn load(args: &Args) -> Result<(Config, CacheConfig), ConfigError> {
+    let file: CacheFile = read_file(args.server.config.as_deref())?;
+    let defaults = CacheConfig::default();
+    let cache = CacheConfig {
+        capacity: args
+            .cache_capacity
+            .or(file.cache.capacity)
+            .unwrap_or(defaults.capacity),
+        ttl: args
+            .cache_ttl_secs
+            .or(file.cache.ttl_secs)
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.ttl),
+    };
+    // Report every problem at once, the server's and the cache's.
+    let mut problems = Vec::new();
+    let config = match Config::from_sources(&args.server, file.server) {
+        Ok(config) => Some(config),
+        Err(ConfigError::Invalid(server_problems)) => {
+            problems = server_problems;
+            None
+        }
+        Err(error) => return Err(error),
+    };
+    if cache.capacity == 0 {
+        problems.push("cache capacity must be at least 1".to_string());
+    }
+    match config {
+        Some(config) if problems.is_empty() => Ok((config, cache)),
+        _ => Err(ConfigError::Invalid(problems)),
+    }
+}
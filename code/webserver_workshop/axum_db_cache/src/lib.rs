@@ -0,0 +1,66 @@
+use axum::extract::FromRef;
+use axum::routing::get;
+use axum::{middleware, Router};
+use axum_db::events::{self, Events};
+use axum_db::metrics::{self, Metrics};
+use axum_db::repository::{PersonRepository, SqliteRepository};
+use axum_db::Config;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+pub mod cache;
+pub mod config;
+mod person;
+
+pub use cache::{CacheStats, PersonCache};
+
+/// Everything the handlers share. Handlers extract just the parts they
+/// need, e.g. `State(cache): State<Arc<PersonCache>>`.
+///
+/// As in `axum_db`, `pool` holds the API keys and `people` holds the
+/// people - by default, in the same database.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub people: Arc<dyn PersonRepository>,
+    pub cache: Arc<PersonCache>,
+    pub config: Arc<Config>,
+    pub metrics: Metrics,
+    pub events: Events,
+}
+
+impl AppState {
+    /// State with the default `Config`, keeping people in `pool`.
+    pub fn new(pool: SqlitePool, cache: Arc<PersonCache>) -> Self {
+        Self::with_config(pool, cache, Config::default())
+    }
+
+    pub fn with_config(pool: SqlitePool, cache: Arc<PersonCache>, config: Config) -> Self {
+        let metrics = Metrics::new();
+        cache.register_metrics(&metrics);
+        Self {
+            people: Arc::new(SqliteRepository::new(pool.clone())),
+            pool,
+            cache,
+            config: Arc::new(config),
+            metrics,
+            events: Events::default(),
+        }
+    }
+}
+
+/// Builds the application's router. `main` serves it; the tests call it
+/// directly, without binding a socket.
+pub fn app(state: AppState) -> Router {
+    let router = Router::new()
+        .merge(person::routes())
+        .merge(events::routes())
+        .route("/metrics", get(metrics::scrape));
+    let router = axum_db::limits::apply(router, &state.config.limits)
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            metrics::track,
+        ))
+        .with_state(state);
+    axum_db::telemetry::with_tracing(router)
+}
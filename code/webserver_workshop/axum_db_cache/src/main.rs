@@ -1,71 +1,59 @@
-use std::{collections::HashMap, sync::Arc};
-
-use axum::{extract::Path, routing::get, Extension, Json, Router};
+use axum_db::shutdown;
+use axum_db_cache::config::{self, Args};
+use axum_db_cache::{AppState, PersonCache};
+use clap::Parser;
 
 #[tokio::main]
 async fn main() {
     // Run dotenvy
     let _ = dotenvy::dotenv(); // It's ok to not have a .env file
-    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let connection_pool = sqlx::SqlitePool::connect(&database_url)
-        .await.unwrap();
-
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3001").await.unwrap();
-
-    let app = Router::new()
-        .route("/person/:id", get(get_person))
-        .layer(Extension(connection_pool))
-        .layer(Extension(PersonCache::new()));
-
-    axum::serve(listener, app).await.unwrap();
-}
-
-#[derive(serde::Serialize, sqlx::FromRow, Clone)]
-struct Person {
-    id: i32,
-    name: String,
-    age: i32,
-}
-
-struct PersonCache {
-    cache: tokio::sync::Mutex<HashMap<i32, Person>>,
-}
-
-impl PersonCache {
-    fn new() -> Arc<Self> {
-        Arc::new(Self {
-            cache: tokio::sync::Mutex::new(HashMap::new()),
-        })
-    }
-
-    async fn add(&self, person: Person) {
-        let mut cache = self.cache.lock().await;
-        cache.insert(person.id, person);
+    let args = Args::parse();
+    let (config, cache_config) = config::load(&args).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        std::process::exit(2);
+    });
+    axum_db::telemetry::init(&config.log_filter, config.log_format);
+    println!("Effective configuration:\n{config}{cache_config}");
+
+    let connection_pool = axum_db::db::connect(&config.database).await.unwrap();
+
+    // Bring the schema up to date before we serve anything.
+    axum_db::db::migrate(&connection_pool).await.unwrap();
+    if args.server.migrate_only {
+        println!("Migrations applied to {}", config.database.url);
+        return;
     }
 
-    async fn get(&self, id: i32) -> Option<Person> {
-        let cache = self.cache.lock().await;
-        cache.get(&id).cloned()
-    }
-}
-
-async fn get_person(
-    Path(id): Path<i32>,
-    Extension(pool): Extension<sqlx::SqlitePool>,
-    Extension(cache): Extension<Arc<PersonCache>>,
-) -> Json<Person> {
-    if let Some(person) = cache.get(id).await {
-        println!("Cache Hit");
-        return Json(person);
-    } else {
-        println!("Cache Miss");
-        let person: Person = sqlx::query_as("SELECT * FROM my_data WHERE id = ?")
-            .bind(id)
-            .fetch_one(&pool)
+    if let Some(name) = &args.server.create_api_key {
+        axum_db::auth::create_and_print_api_key(&connection_pool, name, &args.server.scopes)
             .await
             .unwrap();
-
-        cache.add(person.clone()).await;
-        Json(person)
+        return;
     }
-}
\ No newline at end of file
+
+    let listener = tokio::net::TcpListener::bind(config.bind_address)
+        .await
+        .unwrap();
+
+    let cache = PersonCache::new(cache_config.capacity, cache_config.ttl);
+    let people = axum_db::repository::connect(&config.database, &connection_pool)
+        .await
+        .unwrap();
+    let state = AppState {
+        people: people.clone(),
+        ..AppState::with_config(connection_pool.clone(), cache, config.clone())
+    };
+    let events = state.events.clone();
+    let app = axum_db_cache::app(state);
+
+    // The event streams never finish by themselves, so we end them.
+    let signal = async move {
+        shutdown::signal().await;
+        events.close();
+    };
+    let drain = shutdown::serve(listener, app, signal, config.drain_timeout)
+        .await
+        .unwrap();
+    people.close(drain).await;
+    shutdown::close_pool(&connection_pool, drain).await;
+}
@@ -0,0 +1,129 @@
+use crate::cache::{CacheStats, PersonCache};
+use crate::AppState;
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::Router;
+use axum_db::auth::{Reader, Writer};
+use axum_db::events::{Events, PersonEvent};
+use axum_db::{AppError, Json, NewPerson, Path, Person, PersonPatch, PersonRepository};
+use std::sync::Arc;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/person", post(create_person))
+        .route(
+            "/person/:id",
+            get(get_person)
+                .put(replace_person)
+                .patch(update_person)
+                .delete(delete_person),
+        )
+        .route("/cache/stats", get(cache_stats))
+}
+
+async fn get_person(
+    _: Reader,
+    Path(id): Path<i32>,
+    State(people): State<Arc<dyn PersonRepository>>,
+    State(cache): State<Arc<PersonCache>>,
+) -> Result<Json<Person>, AppError> {
+    let person = cache
+        .get_or_load(id, || async move {
+            people.get(id).await?.ok_or(AppError::NotFound)
+        })
+        .await?;
+
+    Ok(Json(person))
+}
+
+// The writes below don't put the row they get back into the cache: two
+// writes to the same id can finish in the opposite order to the one they
+// reached the database in, and the older row would stay cached. Dropping
+// the entry is right in either order - the next read loads whatever the
+// database kept. Like axum_db, each one also tells the `/events` and
+// `/ws` subscribers.
+
+async fn create_person(
+    _: Writer,
+    State(people): State<Arc<dyn PersonRepository>>,
+    State(events): State<Events>,
+    Json(new_person): Json<NewPerson>,
+) -> Result<impl IntoResponse, AppError> {
+    new_person.validate()?;
+
+    // A new id can't be cached yet, so there's nothing to drop.
+    let person = people.create(&new_person).await?;
+    events.publish(PersonEvent::Created {
+        person: person.clone(),
+    });
+    let location = format!("/person/{}", person.id);
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(person),
+    ))
+}
+
+async fn replace_person(
+    _: Writer,
+    Path(id): Path<i32>,
+    State(people): State<Arc<dyn PersonRepository>>,
+    State(cache): State<Arc<PersonCache>>,
+    State(events): State<Events>,
+    Json(new_person): Json<NewPerson>,
+) -> Result<Json<Person>, AppError> {
+    new_person.validate()?;
+
+    let person = people
+        .replace(id, &new_person)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    cache.invalidate(id).await;
+    events.publish(PersonEvent::Updated {
+        person: person.clone(),
+    });
+    Ok(Json(person))
+}
+
+async fn update_person(
+    _: Writer,
+    Path(id): Path<i32>,
+    State(people): State<Arc<dyn PersonRepository>>,
+    State(cache): State<Arc<PersonCache>>,
+    State(events): State<Events>,
+    Json(patch): Json<PersonPatch>,
+) -> Result<Json<Person>, AppError> {
+    patch.validate()?;
+
+    let person = people.update(id, &patch).await?.ok_or(AppError::NotFound)?;
+
+    cache.invalidate(id).await;
+    events.publish(PersonEvent::Updated {
+        person: person.clone(),
+    });
+    Ok(Json(person))
+}
+
+async fn delete_person(
+    _: Writer,
+    Path(id): Path<i32>,
+    State(people): State<Arc<dyn PersonRepository>>,
+    State(cache): State<Arc<PersonCache>>,
+    State(events): State<Events>,
+) -> Result<StatusCode, AppError> {
+    let deleted = people.delete(id).await?;
+
+    cache.invalidate(id).await;
+    if !deleted {
+        return Err(AppError::NotFound);
+    }
+    events.publish(PersonEvent::Deleted { id });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn cache_stats(State(cache): State<Arc<PersonCache>>) -> Json<CacheStats> {
+    Json(cache.stats().await)
+}
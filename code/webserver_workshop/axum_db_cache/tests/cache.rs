@@ -0,0 +1,198 @@
+use axum_db::{AppError, Person};
+use axum_db_cache::PersonCache;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+fn person(id: i32) -> Person {
+    Person {
+        id,
+        name: format!("Person {id}"),
+        age: 30,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn entries_expire_after_the_ttl() {
+    let cache = PersonCache::new(10, Duration::from_secs(60));
+    cache.add(person(1)).await;
+
+    tokio::time::advance(Duration::from_secs(59)).await;
+    assert_eq!(cache.get(1).await, Some(person(1)));
+
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert_eq!(cache.get(1).await, None);
+
+    let stats = cache.stats().await;
+    assert_eq!((stats.hits, stats.misses, stats.expirations), (1, 1, 1));
+    assert_eq!(stats.size, 0);
+}
+
+#[tokio::test]
+async fn full_cache_evicts_the_least_recently_used() {
+    let cache = PersonCache::new(2, Duration::from_secs(60));
+    cache.add(person(1)).await;
+    cache.add(person(2)).await;
+
+    // Reading 1 makes 2 the least recently used...
+    assert!(cache.get(1).await.is_some());
+    cache.add(person(3)).await;
+
+    // ...so 2 is the one that goes.
+    assert!(cache.get(2).await.is_none());
+    assert!(cache.get(1).await.is_some());
+    assert!(cache.get(3).await.is_some());
+    let stats = cache.stats().await;
+    assert_eq!((stats.size, stats.evictions), (2, 1));
+}
+
+#[tokio::test]
+async fn replacing_an_entry_does_not_evict() {
+    let cache = PersonCache::new(2, Duration::from_secs(60));
+    cache.add(person(1)).await;
+    cache.add(person(2)).await;
+    cache
+        .add(Person {
+            age: 31,
+            ..person(1)
+        })
+        .await;
+
+    assert_eq!(cache.get(1).await.unwrap().age, 31);
+    assert!(cache.get(2).await.is_some());
+    assert_eq!(cache.stats().await.evictions, 0);
+}
+
+#[tokio::test]
+async fn invalidate_removes_the_entry() {
+    let cache = PersonCache::new(10, Duration::from_secs(60));
+    cache.add(person(1)).await;
+    cache.invalidate(1).await;
+    cache.invalidate(2).await;
+
+    assert!(cache.get(1).await.is_none());
+    assert_eq!(cache.stats().await.invalidations, 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_misses_share_one_load() {
+    let cache = PersonCache::new(10, Duration::from_secs(60));
+    let loads = Arc::new(AtomicUsize::new(0));
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..100 {
+        let cache = cache.clone();
+        let loads = loads.clone();
+        tasks.spawn(async move {
+            cache
+                .get_or_load(1, || async move {
+                    loads.fetch_add(1, Ordering::SeqCst);
+                    // Slow enough that everyone piles up behind us.
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Ok(person(1))
+                })
+                .await
+        });
+    }
+    while let Some(result) = tasks.join_next().await {
+        assert_eq!(result.unwrap().unwrap(), person(1));
+    }
+
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    let stats = cache.stats().await;
+    assert_eq!(stats.loads, 1);
+    assert_eq!(stats.coalesced + stats.hits, 99);
+}
+
+#[tokio::test]
+async fn a_miss_as_a_load_finishes_does_not_load_again() {
+    let cache = PersonCache::new(10, Duration::from_secs(60));
+    let loads = Arc::new(AtomicUsize::new(0));
+    let resolved = Arc::new(AtomicBool::new(false));
+
+    // The second miss comes along once the first load has its answer, but
+    // before the cache has filed it.
+    let second = tokio::spawn({
+        let (cache, loads, resolved) = (cache.clone(), loads.clone(), resolved.clone());
+        async move {
+            while !resolved.load(Ordering::SeqCst) {
+                tokio::task::yield_now().await;
+            }
+            cache
+                .get_or_load(1, || async move {
+                    loads.fetch_add(1, Ordering::SeqCst);
+                    Ok(person(1))
+                })
+                .await
+        }
+    });
+
+    let first = cache.get_or_load(1, || async {
+        loads.fetch_add(1, Ordering::SeqCst);
+        resolved.store(true, Ordering::SeqCst);
+        // Let the second miss run (the test runtime has one thread).
+        tokio::task::yield_now().await;
+        Ok(person(1))
+    });
+    assert_eq!(first.await.unwrap(), person(1));
+    assert_eq!(second.await.unwrap().unwrap(), person(1));
+
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    let stats = cache.stats().await;
+    assert_eq!((stats.loads, stats.coalesced), (1, 1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_cancelled_load_is_taken_over() {
+    let cache = PersonCache::new(10, Duration::from_secs(60));
+
+    // The request gives up while its query is still running.
+    let abandoned = cache.get_or_load(1, std::future::pending);
+    assert!(tokio::time::timeout(Duration::from_secs(1), abandoned)
+        .await
+        .is_err());
+
+    let next = cache.get_or_load(1, || async { Ok(person(1)) }).await;
+    assert_eq!(next.unwrap(), person(1));
+    assert_eq!(cache.stats().await.loads, 2);
+    assert_eq!(cache.get(1).await, Some(person(1)));
+}
+
+#[tokio::test]
+async fn failed_loads_are_shared_but_not_cached() {
+    let cache = PersonCache::new(10, Duration::from_secs(60));
+
+    let (first, second) = tokio::join!(
+        cache.get_or_load(1, || async {
+            tokio::task::yield_now().await;
+            Err(AppError::NotFound)
+        }),
+        cache.get_or_load(1, || async { panic!("the first load is still running") }),
+    );
+    assert!(matches!(first, Err(AppError::NotFound)));
+    assert!(matches!(second, Err(AppError::NotFound)));
+
+    // Nothing was cached, so the next miss loads again.
+    let third = cache.get_or_load(1, || async { Ok(person(1)) }).await;
+    assert_eq!(third.unwrap(), person(1));
+    assert_eq!(cache.stats().await.loads, 2);
+}
+
+#[tokio::test]
+async fn a_write_during_a_load_wins() {
+    let cache = PersonCache::new(10, Duration::from_secs(60));
+    let updated = Person {
+        age: 99,
+        ..person(1)
+    };
+
+    let load = cache.get_or_load(1, || async {
+        // The row changes while we're still "querying" the old one.
+        cache.add(updated.clone()).await;
+        Ok(person(1))
+    });
+    assert_eq!(load.await.unwrap(), person(1));
+
+    assert_eq!(cache.get(1).await, Some(updated.clone()));
+}
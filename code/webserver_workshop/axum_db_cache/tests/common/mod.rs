@@ -0,0 +1,105 @@
+// Each test file compiles this module separately, so not every file uses
+// every helper. The ones shared with axum_db's tests live in
+// `axum_db::test_util`.
+#![allow(dead_code)]
+
+use axum::async_trait;
+use axum::body::Body;
+use axum::http::Request;
+use axum::Router;
+use axum_db::repository::{PersonFilter, PersonRepository, SqliteRepository};
+use axum_db::{NewPerson, Person, PersonPatch};
+use http_body_util::BodyExt;
+use sqlx::SqlitePool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::Notify;
+use tower::ServiceExt;
+
+pub use axum_db::test_util::*;
+
+/// The whole app, with a fresh database and an empty cache.
+pub async fn test_app() -> Router {
+    let cache = axum_db_cache::PersonCache::new(100, std::time::Duration::from_secs(60));
+    axum_db_cache::app(axum_db_cache::AppState::new(test_pool().await, cache))
+}
+
+/// Sends a GET, and returns the body as text.
+pub async fn get_text(app: Router, uri: &str) -> String {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// The SQLite repository, but it counts the `get`s that reach it. And,
+/// once told to `hold` a name, a `replace` that writes it stops between
+/// writing the row and returning it: it notifies `written`, then waits for
+/// `release`. That's long enough for another write to overtake it.
+pub struct TestRepository {
+    inner: SqliteRepository,
+    pub gets: AtomicUsize,
+    held: Option<String>,
+    pub written: Notify,
+    pub release: Notify,
+}
+
+impl TestRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            inner: SqliteRepository::new(pool),
+            gets: AtomicUsize::new(0),
+            held: None,
+            written: Notify::new(),
+            release: Notify::new(),
+        }
+    }
+
+    pub fn hold(self, name: &str) -> Self {
+        Self {
+            held: Some(name.to_string()),
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl PersonRepository for TestRepository {
+    async fn list(
+        &self,
+        filter: &PersonFilter,
+        limit: u32,
+        offset: u64,
+    ) -> Result<Vec<Person>, sqlx::Error> {
+        self.inner.list(filter, limit, offset).await
+    }
+
+    async fn count(&self, filter: &PersonFilter) -> Result<i64, sqlx::Error> {
+        self.inner.count(filter).await
+    }
+
+    async fn get(&self, id: i32) -> Result<Option<Person>, sqlx::Error> {
+        self.gets.fetch_add(1, Ordering::SeqCst);
+        self.inner.get(id).await
+    }
+
+    async fn create(&self, person: &NewPerson) -> Result<Person, sqlx::Error> {
+        self.inner.create(person).await
+    }
+
+    async fn replace(&self, id: i32, person: &NewPerson) -> Result<Option<Person>, sqlx::Error> {
+        let replaced = self.inner.replace(id, person).await;
+        if self.held.as_ref() == Some(&person.name) {
+            self.written.notify_one();
+            self.release.notified().await;
+        }
+        replaced
+    }
+
+    async fn update(&self, id: i32, patch: &PersonPatch) -> Result<Option<Person>, sqlx::Error> {
+        self.inner.update(id, patch).await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, sqlx::Error> {
+        self.inner.delete(id).await
+    }
+}
@@ -0,0 +1,54 @@
+use axum_db::config::ConfigError;
+use axum_db_cache::config::{self, Args, CacheFile};
+use std::time::Duration;
+
+#[test]
+fn cache_settings_share_the_file() {
+    let file: CacheFile = toml::from_str(
+        r#"
+        bind_address = "0.0.0.0:8080"
+        [cache]
+        capacity = 10
+        "#,
+    )
+    .unwrap();
+    assert_eq!(file.server.bind_address.unwrap().port(), 8080);
+    assert_eq!(file.cache.capacity, Some(10));
+}
+
+#[test]
+fn flags_override_the_defaults() {
+    let args = Args {
+        cache_ttl_secs: Some(5),
+        ..Default::default()
+    };
+    let (config, cache) = config::load(&args).unwrap();
+    assert_eq!(config.bind_address.port(), 3001);
+    assert_eq!(cache.capacity, 1_000);
+    assert_eq!(cache.ttl, Duration::from_secs(5));
+
+    let args = Args {
+        cache_capacity: Some(0),
+        ..Default::default()
+    };
+    assert!(config::load(&args).is_err());
+}
+
+#[test]
+fn server_and_cache_problems_are_reported_together() {
+    let mut args = Args {
+        cache_capacity: Some(0),
+        ..Default::default()
+    };
+    args.server.people_url = Some("mysql://localhost".to_string());
+    let Err(ConfigError::Invalid(problems)) = config::load(&args) else {
+        panic!("expected the config to be invalid");
+    };
+    assert_eq!(
+        problems,
+        [
+            "people_url `mysql://localhost` must be `memory:` or start with `postgres:`",
+            "cache capacity must be at least 1",
+        ]
+    );
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use axum_db::events::PersonEvent;
+use axum_db_cache::{AppState, PersonCache};
+use common::{send, send_as, test_pool, TEST_TOKEN};
+use serde_json::json;
+use std::time::Duration;
+use tower::ServiceExt;
+
+async fn test_state() -> AppState {
+    AppState::new(
+        test_pool().await,
+        PersonCache::new(100, Duration::from_secs(60)),
+    )
+}
+
+#[tokio::test]
+async fn writes_are_published() {
+    let state = test_state().await;
+    let mut subscription = state.events.subscribe();
+    let app = axum_db_cache::app(state);
+
+    let created = send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Carol", "age": 30 })),
+    )
+    .await;
+    let id = created.body["id"].as_i64().unwrap();
+    let uri = format!("/person/{id}");
+    send(
+        app.clone(),
+        Method::PUT,
+        &uri,
+        Some(json!({ "name": "Caroline", "age": 31 })),
+    )
+    .await;
+    send(app.clone(), Method::PATCH, &uri, Some(json!({ "age": 32 }))).await;
+    send(app.clone(), Method::DELETE, &uri, None).await;
+    // Nothing was deleted this time, so there's nothing to tell anyone.
+    let response = send(app.clone(), Method::DELETE, &uri, None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    // Reads aren't published either. (`app` has to outlive the check below:
+    // dropping the last copy of the state closes the subscription.)
+    send(app.clone(), Method::GET, "/person/1", None).await;
+
+    let mut names = Vec::new();
+    for _ in 0..4 {
+        let event = subscription.next().await.unwrap();
+        if let PersonEvent::Created { person } | PersonEvent::Updated { person } = &event {
+            assert_eq!(i64::from(person.id), id);
+        }
+        names.push(event.name());
+    }
+    assert_eq!(names, ["created", "updated", "updated", "deleted"]);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), subscription.next())
+            .await
+            .is_err(),
+        "nothing else should have been published"
+    );
+}
+
+#[tokio::test]
+async fn the_event_streams_are_served() {
+    let app = axum_db_cache::app(test_state().await);
+
+    let response = send_as(app.clone(), None, Method::GET, "/events", None).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    // The stream never ends, so only look at the head.
+    let request = Request::builder()
+        .uri("/events")
+        .header(header::AUTHORIZATION, format!("Bearer {TEST_TOKEN}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/event-stream"
+    );
+}
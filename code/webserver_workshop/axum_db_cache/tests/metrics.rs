@@ -0,0 +1,30 @@
+mod common;
+
+use common::{get, test_app};
+
+#[tokio::test]
+async fn cache_metrics_are_exported() {
+    let app = test_app().await;
+    get(app.clone(), "/person/1").await;
+    get(app.clone(), "/person/1").await;
+    get(app.clone(), "/person/2").await;
+
+    // `get` expects JSON, so fetch the text by hand.
+    let body = common::get_text(app, "/metrics").await;
+    let value = |series: &str| -> f64 {
+        let line = body
+            .lines()
+            .find(|line| line.starts_with(&format!("{series} ")))
+            .unwrap();
+        line.rsplit_once(' ').unwrap().1.parse().unwrap()
+    };
+    assert_eq!(value("person_cache_hits_total"), 1.0);
+    assert_eq!(value("person_cache_misses_total"), 2.0);
+    assert_eq!(value("person_cache_loads_total"), 2.0);
+    assert_eq!(value("person_cache_size"), 2.0);
+    assert_eq!(value("person_cache_capacity"), 100.0);
+    assert_eq!(
+        value(r#"http_requests_total{method="GET",route="/person/:id",status="200"}"#),
+        3.0
+    );
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use axum_db::repository::MemoryRepository;
+use axum_db_cache::{AppState, PersonCache};
+use common::{get, send, send_as, test_app, test_pool, TestRepository};
+use serde_json::json;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn second_read_is_a_hit() {
+    let app = test_app().await;
+
+    get(app.clone(), "/person/1").await;
+    let response = get(app.clone(), "/person/1").await;
+    assert_eq!(response.body["name"], "Alice");
+
+    let stats = get(app, "/cache/stats").await.body;
+    assert_eq!(stats["hits"], 1);
+    assert_eq!(stats["misses"], 1);
+    assert_eq!(stats["size"], 1);
+}
+
+#[tokio::test]
+async fn the_cache_needs_a_key_too() {
+    let app = test_app().await;
+    get(app.clone(), "/person/1").await;
+
+    // A cached person is still only for callers with a `read` key.
+    let response = send_as(app.clone(), None, Method::GET, "/person/1", None).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    let response = send_as(
+        app.clone(),
+        Some("not a key"),
+        Method::DELETE,
+        "/person/1",
+        None,
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let stats = get(app, "/cache/stats").await.body;
+    assert_eq!(stats["size"], 1);
+    assert_eq!(stats["invalidations"], 0);
+}
+
+#[tokio::test]
+async fn writes_invalidate() {
+    let app = test_app().await;
+    get(app.clone(), "/person/1").await;
+
+    send(
+        app.clone(),
+        Method::PUT,
+        "/person/1",
+        Some(json!({ "name": "Alicia", "age": 43 })),
+    )
+    .await;
+    let response = get(app.clone(), "/person/1").await;
+    assert_eq!(response.body["name"], "Alicia");
+
+    send(
+        app.clone(),
+        Method::PATCH,
+        "/person/1",
+        Some(json!({ "age": 44 })),
+    )
+    .await;
+    let response = get(app.clone(), "/person/1").await;
+    assert_eq!(response.body["age"], 44);
+
+    // Each write dropped the entry, so each read after it loaded afresh.
+    let stats = get(app, "/cache/stats").await.body;
+    assert_eq!(stats["hits"], 0);
+    assert_eq!(stats["loads"], 3);
+    assert_eq!(stats["invalidations"], 2);
+}
+
+#[tokio::test]
+async fn racing_writes_never_leave_a_stale_row() {
+    let pool = test_pool().await;
+    let people = Arc::new(TestRepository::new(pool.clone()).hold("First"));
+    let cache = PersonCache::new(100, Duration::from_secs(60));
+    let app = axum_db_cache::app(AppState {
+        people: people.clone(),
+        ..AppState::new(pool, cache)
+    });
+    get(app.clone(), "/person/1").await;
+
+    // The first write reaches the database first, but finishes last.
+    let first = tokio::spawn(send(
+        app.clone(),
+        Method::PUT,
+        "/person/1",
+        Some(json!({ "name": "First", "age": 1 })),
+    ));
+    people.written.notified().await;
+    send(
+        app.clone(),
+        Method::PUT,
+        "/person/1",
+        Some(json!({ "name": "Second", "age": 2 })),
+    )
+    .await;
+    people.release.notify_one();
+    assert_eq!(first.await.unwrap().status, StatusCode::OK);
+
+    // The database kept the second write, so that's what we serve.
+    let response = get(app, "/person/1").await;
+    assert_eq!(response.body["name"], "Second");
+}
+
+#[tokio::test]
+async fn delete_invalidates() {
+    let app = test_app().await;
+    get(app.clone(), "/person/2").await;
+
+    let response = send(app.clone(), Method::DELETE, "/person/2", None).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = get(app.clone(), "/person/2").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(get(app, "/cache/stats").await.body["invalidations"], 1);
+}
+
+#[tokio::test]
+async fn created_people_can_be_read() {
+    let app = test_app().await;
+
+    let response = send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Carol", "age": 30 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let response = get(app.clone(), "/person/3").await;
+    assert_eq!(response.body["name"], "Carol");
+    let response = get(app.clone(), "/person/3").await;
+    assert_eq!(response.body["name"], "Carol");
+    let stats = get(app, "/cache/stats").await.body;
+    assert_eq!(
+        (stats["loads"].as_u64(), stats["hits"].as_u64()),
+        (Some(1), Some(1))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_misses_run_one_query() {
+    let pool = test_pool().await;
+    let people = Arc::new(TestRepository::new(pool.clone()));
+    let cache = PersonCache::new(100, Duration::from_secs(60));
+    let app = axum_db_cache::app(AppState {
+        people: people.clone(),
+        ..AppState::new(pool, cache)
+    });
+
+    let mut requests = tokio::task::JoinSet::new();
+    for _ in 0..100 {
+        let app = app.clone();
+        requests.spawn(async move { get(app, "/person/1").await });
+    }
+    while let Some(response) = requests.join_next().await {
+        assert_eq!(response.unwrap().body["name"], "Alice");
+    }
+
+    // Only one of them reached the database; the rest shared its result
+    // (or came along after, and hit).
+    assert_eq!(people.gets.load(Ordering::SeqCst), 1);
+    let stats = get(app, "/cache/stats").await.body;
+    assert_eq!(stats["loads"], 1);
+    assert_eq!(
+        stats["misses"].as_u64().unwrap(),
+        1 + stats["coalesced"].as_u64().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn people_can_live_elsewhere() {
+    let pool = test_pool().await;
+    let cache = PersonCache::new(100, Duration::from_secs(60));
+    let app = axum_db_cache::app(AppState {
+        people: Arc::new(MemoryRepository::new()),
+        ..AppState::new(pool.clone(), cache)
+    });
+
+    // The memory repository starts empty, whatever SQLite has in it.
+    assert_eq!(
+        get(app.clone(), "/person/1").await.status,
+        StatusCode::NOT_FOUND
+    );
+    let response = send(
+        app.clone(),
+        Method::POST,
+        "/person",
+        Some(json!({ "name": "Carol", "age": 30 })),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+
+    let uri = format!("/person/{}", response.body["id"]);
+    assert_eq!(get(app, &uri).await.body["name"], "Carol");
+    let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM my_data")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows, 2, "Carol shouldn't be in SQLite");
+}
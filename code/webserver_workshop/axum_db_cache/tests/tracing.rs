@@ -0,0 +1,81 @@
+mod common;
+
+use common::{get, test_app};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+// Remembers every `span.field = value` that gets recorded.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<String>>>);
+
+struct Visitor<'a>(&'a str, &'a Mutex<Vec<String>>);
+
+impl Visit for Visitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.1
+            .lock()
+            .unwrap()
+            .push(format!("{}.{} = {value}", self.0, field.name()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.1
+            .lock()
+            .unwrap()
+            .push(format!("{}.{} = {value:?}", self.0, field.name()));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+    fn on_new_span(&self, attrs: &Attributes, _: &Id, _: Context<S>) {
+        attrs.record(&mut Visitor(attrs.metadata().name(), &self.0));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record, ctx: Context<S>) {
+        let name = ctx.span(id).unwrap().name();
+        values.record(&mut Visitor(name, &self.0));
+    }
+}
+
+impl Capture {
+    fn matching(&self, prefix: &str) -> Vec<String> {
+        let lines = self.0.lock().unwrap();
+        lines
+            .iter()
+            .filter(|line| line.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn cache_outcome_and_queries_are_recorded() {
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let app = test_app().await;
+    get(app.clone(), "/person/1").await;
+    get(app, "/person/1").await;
+
+    assert_eq!(
+        capture.matching("request.cache"),
+        vec!["request.cache = miss", "request.cache = hit"]
+    );
+    // One person query (the API key lookups are queries too), and every
+    // query is timed.
+    assert_eq!(
+        capture.matching("db.query.operation = select person").len(),
+        1
+    );
+    assert_eq!(
+        capture.matching("db.query.elapsed_ms").len(),
+        capture.matching("db.query.operation").len()
+    );
+    assert_eq!(capture.matching("request.request_id").len(), 2);
+}
@@ -0,0 +1,21 @@
+use axum::{extract::Path, response::Html, routing::get, Router};
+
+/// Builds the application's router. `main` serves it; the tests call it
+/// directly, without binding a socket.
+pub fn app() -> Router {
+    Router::new()
+        .route("/", get(say_hello))
+        .route("/hello/:n", get(html_path))
+}
+
+async fn say_hello() -> &'static str {
+    "Hello, World!"
+}
+
+async fn html_path(
+    Path(n): Path<u32>,
+) -> Html<String> {
+    let base = include_str!("hello.html");
+    let templated = base.replace("$$MYPICK$$", &n.to_string());
+    Html(templated)
+}
@@ -0,0 +1,23 @@
+use axum::http::StatusCode;
+use workshop_util::testing;
+
+// `/` is the same as in axum_hello_world, and tested there.
+
+async fn get(uri: &str) -> (StatusCode, String, String) {
+    testing::get(axum_hello_html::app(), uri).await
+}
+
+#[tokio::test]
+async fn hello_page_includes_the_number() {
+    let (status, content_type, body) = get("/hello/42").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.starts_with("text/html"));
+    assert!(body.contains("You picked 42"));
+    assert!(!body.contains("$$MYPICK$$"));
+}
+
+#[tokio::test]
+async fn hello_page_rejects_non_numbers() {
+    let (status, _, _) = get("/hello/bob").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
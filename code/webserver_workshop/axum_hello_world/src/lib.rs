@@ -0,0 +1,12 @@
+use axum::{routing::get, Router};
+
+/// Builds the application's router. `main` serves it; the tests call it
+/// directly, without binding a socket.
+pub fn app() -> Router {
+    Router::new()
+        .route("/", get(say_hello))
+}
+
+async fn say_hello() -> &'static str {
+    "Hello, World!"
+}
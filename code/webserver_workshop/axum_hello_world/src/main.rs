@@ -1,15 +1,11 @@
-use axum::{routing::get, Router};
-
 #[tokio::main]
 async fn main() {
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3001").await.unwrap();
+    let listener = tokio::net::TcpListener::bind(workshop_util::bind_address()).await.unwrap();
 
-    let app = Router::new()
-        .route("/", get(say_hello));
+    let app = axum_hello_world::app();
 
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(workshop_util::shutdown::signal())
+        .await
+        .unwrap();
 }
-
-async fn say_hello() -> &'static str {
-    "Hello, World!"
-}
\ No newline at end of file
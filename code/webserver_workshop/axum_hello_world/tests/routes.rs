@@ -0,0 +1,19 @@
+use axum::http::StatusCode;
+use workshop_util::testing;
+
+async fn get(uri: &str) -> (StatusCode, String, String) {
+    testing::get(axum_hello_world::app(), uri).await
+}
+
+#[tokio::test]
+async fn root_says_hello() {
+    let (status, _, body) = get("/").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "Hello, World!");
+}
+
+#[tokio::test]
+async fn unknown_routes_are_404() {
+    let (status, _, _) = get("/nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,71 @@
+use axum::{extract::Path, response::Html, routing::get, Json, Router};
+use utoipa::OpenApi;
+
+/// Builds the application's router. `main` serves it; the tests call it
+/// directly, without binding a socket.
+pub fn app() -> Router {
+    Router::new()
+        .route("/", get(say_hello))
+        .route("/hello/:n", get(html_path))
+        .route("/json/:n", get(json_path))
+        .route("/openapi.json", get(openapi_json))
+}
+
+/// The OpenAPI document for the JSON route, generated from `json_path`'s
+/// `#[utoipa::path]` and `MyData`'s `ToSchema`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "axum_json", description = "The webserver workshop's JSON example."),
+    paths(json_path),
+    components(schemas(MyData)),
+    modifiers(&NoLicense)
+)]
+pub struct ApiDoc;
+
+// utoipa copies the license from `Cargo.toml`, and we don't have one.
+struct NoLicense;
+
+impl utoipa::Modify for NoLicense {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.license = None;
+    }
+}
+
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+async fn say_hello() -> &'static str {
+    "Hello, World!"
+}
+
+async fn html_path(
+    Path(n): Path<u32>,
+) -> Html<String> {
+    let base = include_str!("hello.html");
+    let templated = base.replace("$$MYPICK$$", &n.to_string());
+    Html(templated)
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+struct MyData {
+    name: String,
+    age: u32,
+}
+
+/// A made-up person, of whatever age you ask for.
+#[utoipa::path(
+    get,
+    path = "/json/{n}",
+    tag = "examples",
+    params(("n" = u32, Path, description = "Their age")),
+    responses((status = 200, body = MyData)),
+)]
+async fn json_path(
+    Path(n): Path<u32>,
+) -> axum::Json<MyData> {
+    axum::Json(MyData {
+        name: "Alice".to_string(),
+        age: n,
+    })
+}
@@ -0,0 +1,32 @@
+use axum::http::StatusCode;
+use utoipa::OpenApi;
+use workshop_util::testing;
+
+// `/` and `/hello/:n` are the same as in axum_hello_html, and tested there.
+
+async fn get(uri: &str) -> (StatusCode, String, String) {
+    testing::get(axum_json::app(), uri).await
+}
+
+#[tokio::test]
+async fn json_returns_alice() {
+    let (status, content_type, body) = get("/json/7").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    assert_eq!(body, r#"{"name":"Alice","age":7}"#);
+}
+
+// The document is checked in. To accept a change, run the tests with
+// `UPDATE_SNAPSHOTS=1`.
+#[tokio::test]
+async fn openapi_matches_the_snapshot() {
+    let (status, content_type, body) = get("/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    let served: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(served, serde_json::to_value(axum_json::ApiDoc::openapi()).unwrap());
+
+    let spec = axum_json::ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+    let snapshot = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/openapi.json");
+    testing::assert_snapshot(&snapshot, &spec);
+}
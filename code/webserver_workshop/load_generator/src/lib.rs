@@ -0,0 +1,185 @@
+use rand::Rng;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+pub use latency_stats::percentile;
+
+/// How to pick which person to ask for.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    /// Every id is equally likely.
+    Uniform,
+    /// A few ids are very popular, and most are rarely asked for - which
+    /// is what real traffic tends to look like, and what caches love.
+    Zipf,
+}
+
+/// Picks ids from `1..=max_id`.
+pub enum IdSampler {
+    Uniform(u32),
+    // The cumulative probability of each id, so a sample is a binary
+    // search for a random number in `0.0..1.0`.
+    Zipf(Vec<f64>),
+}
+
+impl IdSampler {
+    /// `exponent` is only used by `Zipf`: the bigger it is, the more the
+    /// first few ids dominate.
+    pub fn new(distribution: Distribution, max_id: u32, exponent: f64) -> Self {
+        assert!(max_id > 0, "max_id must be at least 1");
+        match distribution {
+            Distribution::Uniform => IdSampler::Uniform(max_id),
+            Distribution::Zipf => {
+                let weights: Vec<f64> = (1..=max_id).map(|rank| 1.0 / (rank as f64).powf(exponent)).collect();
+                let total: f64 = weights.iter().sum();
+                let mut cumulative = 0.0;
+                let cdf = weights
+                    .iter()
+                    .map(|weight| {
+                        cumulative += weight / total;
+                        cumulative
+                    })
+                    .collect();
+                IdSampler::Zipf(cdf)
+            }
+        }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> u32 {
+        match self {
+            IdSampler::Uniform(max_id) => rng.gen_range(1..=*max_id),
+            IdSampler::Zipf(cdf) => {
+                let x: f64 = rng.gen();
+                // Rounding can leave the last entry a hair under 1.0.
+                let index = cdf.partition_point(|&p| p < x).min(cdf.len() - 1);
+                index as u32 + 1
+            }
+        }
+    }
+}
+
+/// What to send, and for how long.
+pub struct Settings {
+    /// A URL with an `{id}` placeholder, e.g.
+    /// `http://127.0.0.1:3001/person/{id}`.
+    pub url: String,
+    pub sampler: IdSampler,
+    /// How many requests to keep in flight - one connection each.
+    pub concurrency: usize,
+    pub duration: Duration,
+    /// An API key, sent as a bearer token.
+    pub token: Option<String>,
+}
+
+pub struct Report {
+    pub requests: usize,
+    /// Failed connections, and responses that weren't a `2xx`.
+    pub errors: usize,
+    pub elapsed: Duration,
+    /// Sorted, so percentiles are just an index.
+    pub latencies: Vec<Duration>,
+}
+
+impl Report {
+    pub fn throughput(&self) -> f64 {
+        self.requests as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn percentile(&self, pct: f64) -> Duration {
+        percentile(&self.latencies, pct)
+    }
+}
+
+/// Runs the load test: `concurrency` workers, each with its own client
+/// (and so its own connection), sending requests back to back until the
+/// time is up.
+pub async fn run(settings: Settings) -> Report {
+    let settings = std::sync::Arc::new(settings);
+    let start = Instant::now();
+    let deadline = start + settings.duration;
+
+    let mut workers = JoinSet::new();
+    for _ in 0..settings.concurrency {
+        let settings = settings.clone();
+        workers.spawn(async move {
+            let client = reqwest::Client::builder().pool_max_idle_per_host(1).build().unwrap();
+            let mut latencies = Vec::new();
+            let mut errors = 0;
+            while Instant::now() < deadline {
+                // `ThreadRng` isn't `Send`, so don't hold it across the `.await`.
+                let id = settings.sampler.sample(&mut rand::thread_rng());
+                let url = settings.url.replace("{id}", &id.to_string());
+
+                let sent = Instant::now();
+                let mut request = client.get(&url);
+                if let Some(token) = &settings.token {
+                    request = request.bearer_auth(token);
+                }
+                let ok = match request.send().await {
+                    Ok(response) => {
+                        let success = response.status().is_success();
+                        // Read the whole body - that's part of the request, too.
+                        response.bytes().await.is_ok() && success
+                    }
+                    Err(_) => false,
+                };
+                latencies.push(sent.elapsed());
+                if !ok {
+                    errors += 1;
+                }
+            }
+            (latencies, errors)
+        });
+    }
+
+    let mut latencies = Vec::new();
+    let mut errors = 0;
+    while let Some(result) = workers.join_next().await {
+        let (worker_latencies, worker_errors) = result.unwrap();
+        latencies.extend(worker_latencies);
+        errors += worker_errors;
+    }
+    let elapsed = start.elapsed();
+    latencies.sort();
+
+    Report {
+        requests: latencies.len(),
+        errors,
+        elapsed,
+        latencies,
+    }
+}
+
+/// Makes sure an `axum_db`-style server has people with ids up to
+/// `max_id`, by `POST`ing to `{base}/person` until it does. Returns how
+/// many people it added. `token` needs the `read` and `write` scopes.
+pub async fn seed(base: &str, max_id: u32, token: &str) -> Result<u32, reqwest::Error> {
+    let client = reqwest::Client::new();
+    let last = client
+        .get(format!("{base}/person/{max_id}"))
+        .bearer_auth(token)
+        .send()
+        .await?;
+    if last.status().is_success() {
+        return Ok(0);
+    }
+
+    // Ids are handed out in order, so keep adding until we get there.
+    let mut added = 0;
+    loop {
+        let person = serde_json::json!({ "name": format!("Person {}", added + 1), "age": added % 100 });
+        let created: serde_json::Value = client
+            .post(format!("{base}/person"))
+            .bearer_auth(token)
+            .json(&person)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        added += 1;
+        if created["id"].as_u64().unwrap_or(0) >= max_id as u64 {
+            return Ok(added);
+        }
+    }
+}
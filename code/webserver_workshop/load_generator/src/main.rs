@@ -0,0 +1,91 @@
+// Hammers one or more servers with `GET /person/{id}` requests, and
+// prints a Markdown table comparing them. For example, with `axum_db` on
+// port 3001 and `axum_db_cache` on 3002:
+//
+// cargo run --release -p load_generator -- --token <key> --seed \
+//     http://127.0.0.1:3001 http://127.0.0.1:3002
+use clap::Parser;
+use latency_stats::millis;
+use load_generator::{Distribution, IdSampler, Settings};
+use std::time::Duration;
+
+#[derive(Parser)]
+struct Args {
+    /// Base URLs of the servers to test, one after the other.
+    #[arg(required = true)]
+    targets: Vec<String>,
+    /// The path to request. `{id}` is replaced with a person's id.
+    #[arg(long, default_value = "/person/{id}")]
+    path: String,
+    /// Ask for ids from 1 up to this.
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+    max_id: u32,
+    #[arg(long, value_enum, default_value_t = Distribution::Zipf)]
+    distribution: Distribution,
+    /// How skewed the zipf distribution is.
+    #[arg(long, default_value_t = 1.0)]
+    zipf_exponent: f64,
+    /// Concurrent connections.
+    #[arg(long, default_value_t = 32)]
+    concurrency: usize,
+    /// How long to test each target for.
+    #[arg(long, default_value_t = 10)]
+    duration_secs: u64,
+    /// First add people until every id up to `--max-id` exists. Needs
+    /// a `--token` with the `write` scope.
+    #[arg(long, requires = "token")]
+    seed: bool,
+    /// The API key to send - create one with the server's
+    /// `--create-api-key`.
+    #[arg(long, env = "API_TOKEN")]
+    token: Option<String>,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let mut rows = Vec::new();
+    for target in &args.targets {
+        let target = target.trim_end_matches('/');
+        if args.seed {
+            // Clap makes sure there's a `--token` with `--seed`.
+            let token = args.token.as_deref().unwrap();
+            match load_generator::seed(target, args.max_id, token).await {
+                Ok(added) => eprintln!("{target}: added {added} people"),
+                Err(error) => {
+                    eprintln!("{target}: seeding failed: {error}");
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        eprintln!("{target}: running for {} seconds...", args.duration_secs);
+        let report = load_generator::run(Settings {
+            url: format!("{target}{}", args.path),
+            sampler: IdSampler::new(args.distribution, args.max_id, args.zipf_exponent),
+            concurrency: args.concurrency,
+            duration: Duration::from_secs(args.duration_secs),
+            token: args.token.clone(),
+        })
+        .await;
+        rows.push(format!(
+            "{}|{}|{}|{:.0}|{:.3}|{:.3}|{:.3}|{:.3}|{:.3}",
+            target,
+            report.requests,
+            report.errors,
+            report.throughput(),
+            millis(report.percentile(50.0)),
+            millis(report.percentile(90.0)),
+            millis(report.percentile(99.0)),
+            millis(report.percentile(99.9)),
+            millis(report.percentile(100.0)),
+        ));
+    }
+
+    println!("Target|Requests|Errors|Req/s|p50 (ms)|p90 (ms)|p99 (ms)|p99.9 (ms)|max (ms)");
+    println!("--|--|--|--|--|--|--|--|--");
+    for row in rows {
+        println!("{row}");
+    }
+}
@@ -0,0 +1,14 @@
+use std::process::Command;
+
+// `Args` lives in `main.rs`, so this runs the real binary.
+#[test]
+fn max_id_must_be_at_least_one() {
+    let output = Command::new(env!("CARGO_BIN_EXE_load_generator"))
+        .args(["--max-id", "0", "http://127.0.0.1:1"])
+        .output()
+        .unwrap();
+    // Clap's usage errors exit with 2, before anything is sent.
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--max-id"), "{stderr}");
+}
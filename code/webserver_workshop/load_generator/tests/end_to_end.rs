@@ -0,0 +1,71 @@
+use axum_db::auth::Scope;
+use axum_db::config::{Config, LimitsConfig};
+use axum_db_cache::{AppState, PersonCache};
+use load_generator::{Distribution, IdSampler, Settings};
+use sqlx::sqlite::SqlitePoolOptions;
+use std::time::Duration;
+
+const TOKEN: &str = "load-test-token";
+
+// Serves `axum_db_cache` over an in-memory database, on a free port.
+async fn start_server() -> String {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    axum_db::db::migrate(&pool).await.unwrap();
+    axum_db::auth::add_api_key(&pool, "load test", TOKEN, &[Scope::Read, Scope::Write])
+        .await
+        .unwrap();
+    let cache = PersonCache::new(100, Duration::from_secs(60));
+    // We're measuring the server, not its rate limiter.
+    let config = Config {
+        limits: LimitsConfig::none(),
+        ..Config::default()
+    };
+    let app = axum_db_cache::app(AppState::with_config(pool, cache, config));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{address}")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn seeds_and_measures_a_real_server() {
+    let base = start_server().await;
+
+    assert_eq!(load_generator::seed(&base, 20, TOKEN).await.unwrap(), 18);
+    assert_eq!(load_generator::seed(&base, 20, TOKEN).await.unwrap(), 0);
+
+    let report = load_generator::run(Settings {
+        url: format!("{base}/person/{{id}}"),
+        sampler: IdSampler::new(Distribution::Zipf, 20, 1.0),
+        concurrency: 4,
+        duration: Duration::from_millis(300),
+        token: Some(TOKEN.to_string()),
+    })
+    .await;
+    assert!(report.requests > 0);
+    assert_eq!(report.errors, 0);
+    assert!(report.percentile(50.0) <= report.percentile(99.9));
+}
+
+#[tokio::test]
+async fn requests_without_a_key_are_errors() {
+    let base = start_server().await;
+    assert!(load_generator::seed(&base, 5, "wrong").await.is_err());
+
+    let report = load_generator::run(Settings {
+        url: format!("{base}/person/{{id}}"),
+        sampler: IdSampler::new(Distribution::Uniform, 2, 1.0),
+        concurrency: 1,
+        duration: Duration::from_millis(50),
+        token: None,
+    })
+    .await;
+    assert_eq!(report.errors, report.requests);
+}
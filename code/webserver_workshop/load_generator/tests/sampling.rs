@@ -0,0 +1,30 @@
+use load_generator::{Distribution, IdSampler};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn histogram(sampler: &IdSampler, max_id: u32) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut counts = vec![0; max_id as usize + 1];
+    for _ in 0..100_000 {
+        counts[sampler.sample(&mut rng) as usize] += 1;
+    }
+    counts
+}
+
+#[test]
+fn uniform_stays_in_range() {
+    let counts = histogram(&IdSampler::new(Distribution::Uniform, 10, 1.0), 10);
+    assert_eq!(counts[0], 0);
+    // Every id gets roughly a tenth.
+    assert!(counts[1..].iter().all(|&count| (9_000..11_000).contains(&count)), "{counts:?}");
+}
+
+#[test]
+fn zipf_favours_the_first_ids() {
+    let counts = histogram(&IdSampler::new(Distribution::Zipf, 100, 1.0), 100);
+    assert_eq!(counts[0], 0);
+    // With an exponent of 1, id 1 is asked for twice as often as id 2.
+    let ratio = counts[1] as f64 / counts[2] as f64;
+    assert!((1.8..2.2).contains(&ratio), "ratio was {ratio}");
+    assert!(counts[1] > counts[100] * 50);
+}
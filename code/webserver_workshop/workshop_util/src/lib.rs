@@ -0,0 +1,23 @@
+//! Bits the workshop's servers would otherwise each carry a copy of.
+
+use clap::Parser;
+use std::net::SocketAddr;
+
+pub mod shutdown;
+#[cfg(feature = "test-util")]
+pub mod testing;
+
+/// The hello servers' only setting. `axum_db` has many more, but this one
+/// has the same name there.
+#[derive(clap::Parser, Debug)]
+pub struct ServerArgs {
+    /// The address to listen on.
+    #[arg(long, env = "BIND_ADDRESS", default_value = "127.0.0.1:3001")]
+    pub bind_address: SocketAddr,
+}
+
+/// Where to listen: `--bind-address`, `BIND_ADDRESS`, or `127.0.0.1:3001`.
+/// Exits with a usage message if that isn't an address.
+pub fn bind_address() -> SocketAddr {
+    ServerArgs::parse().bind_address
+}
@@ -0,0 +1,31 @@
+//! Graceful shutdown. `axum::serve(listener, app).await` runs until the
+//! process is killed, and Ctrl-C kills it mid-request. Every server here
+//! passes `signal()` to `with_graceful_shutdown` instead: when it fires,
+//! they stop accepting connections and let the requests already running
+//! finish. `axum_db::shutdown` builds on this - it puts a limit on that
+//! wait, then closes the database cleanly.
+
+/// Waits for Ctrl-C (SIGINT) or, on Unix, SIGTERM - which is what
+/// `docker stop`, systemd and Kubernetes send.
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("can't listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("can't listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,41 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use std::path::Path;
+use tower::ServiceExt;
+
+/// Sends one GET to the router, returning the status, content type and body.
+pub async fn get(app: Router, uri: &str) -> (StatusCode, String, String) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        status,
+        content_type,
+        String::from_utf8(bytes.to_vec()).unwrap(),
+    )
+}
+
+/// Checks `actual` against the file at `snapshot`. The file is checked in,
+/// so a change shows up in review as a diff of it. If the change is
+/// intended, run the tests with `UPDATE_SNAPSHOTS=1`: that rewrites the
+/// file instead.
+pub fn assert_snapshot(snapshot: &Path, actual: &str) {
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(snapshot, actual).unwrap();
+    }
+    let expected = std::fs::read_to_string(snapshot).unwrap_or_default();
+    assert_eq!(
+        actual,
+        expected,
+        "{} changed - if that's intended, rerun with UPDATE_SNAPSHOTS=1",
+        snapshot.display()
+    );
+}
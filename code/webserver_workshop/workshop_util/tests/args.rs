@@ -0,0 +1,16 @@
+use clap::Parser;
+use workshop_util::ServerArgs;
+
+#[test]
+fn bind_address_defaults_to_3001() {
+    let args = ServerArgs::try_parse_from(["axum_hello_world"]).unwrap();
+    assert_eq!(args.bind_address.to_string(), "127.0.0.1:3001");
+}
+
+#[test]
+fn bind_address_can_be_changed() {
+    let args =
+        ServerArgs::try_parse_from(["axum_hello_world", "--bind-address", "0.0.0.0:8080"]).unwrap();
+    assert_eq!(args.bind_address.to_string(), "0.0.0.0:8080");
+    assert!(ServerArgs::try_parse_from(["axum_hello_world", "--bind-address", "nope"]).is_err());
+}